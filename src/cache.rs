@@ -0,0 +1,44 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::hash;
+use crate::ndfa::serialization::Serialization;
+use crate::ndfa::Automata;
+
+// The regex is stored too, so hash collisions are not mistaken for hits
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    regex: String,
+    automata: Automata,
+}
+
+impl Serialization for Entry {}
+
+/// None if the automata for the regex was not cached or the entry is broken.
+pub fn load(dir: &Path, regex: &str) -> Option<Automata> {
+    let bytes = fs::read(get_path(dir, regex)).ok()?;
+    let entry = Entry::from_bytes(&bytes).ok()?;
+
+    if entry.regex != regex {
+        return None;
+    }
+
+    entry.automata.validate().ok()
+}
+
+pub fn store(dir: &Path, regex: &str, automata: &Automata) -> Result<(), String> {
+    let entry = Entry {
+        regex: regex.to_string(),
+        automata: automata.clone(),
+    };
+
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    fs::write(get_path(dir, regex), entry.to_bytes()?).map_err(|e| e.to_string())
+}
+
+// The hash is stable, so the cache stays warm across Rust releases
+fn get_path(dir: &Path, regex: &str) -> PathBuf {
+    dir.join(format!("{:016x}.msgpack", hash::stable_hash(regex)))
+}
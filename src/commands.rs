@@ -0,0 +1,351 @@
+use std::fmt;
+use std::path::Path;
+
+use log::info;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::convertor::{Convertor, Decomposition};
+use crate::fuzz::str_generator::StringGenerator;
+use crate::ndfa::ast::Tree;
+use crate::ndfa::dfa::{self, Dfa};
+use crate::ndfa::glushkov::Glushkov;
+use crate::ndfa::printer::Dialect;
+use crate::ndfa::render::RenderFormat;
+use crate::ndfa::trace::TraceFormat;
+use crate::ndfa::{Automata, ConversionMethod, ToRegexOptions};
+use crate::output::{self, Output, OutputFormat};
+use crate::parser::{self, Token};
+
+/// Where a command takes the automata from.
+#[derive(Debug, Clone, Copy)]
+pub enum Input<'a> {
+    Regex(&'a str),
+    /// Labels of an AT&T file are ids of the symbol table if it is given
+    Automata {
+        path: &'a Path,
+        att_symbols: Option<&'a Path>,
+    },
+}
+
+impl Input<'_> {
+    pub fn load(&self, convertor: &mut Convertor) -> Result<Automata, String> {
+        match *self {
+            Self::Regex(r) => convertor.convert(r),
+            Self::Automata {
+                path,
+                att_symbols: Some(symbols_path),
+            } => Automata::read_att(path, Some(symbols_path)),
+            Self::Automata {
+                path,
+                att_symbols: None,
+            } => Automata::read(path),
+        }
+    }
+}
+
+impl fmt::Display for Input<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Regex(r) => write!(f, "{r}"),
+            Self::Automata { path, .. } => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Conversion {
+    pub input: String,
+    /// Of the automata the regex was built from
+    pub states: usize,
+    /// None for the empty language
+    pub regex: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MethodsComparison {
+    pub state_elimination: Option<String>,
+    pub arden: Option<String>,
+    /// None if the regexes are equivalent
+    pub distinguishing_word: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Equivalence {
+    pub equivalent: bool,
+    /// The shortest word accepted by exactly one of the automata
+    pub distinguishing_word: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Minimization {
+    pub states: usize,
+    pub dfa_states: usize,
+    /// Complete, so the trap state is counted too
+    pub dfa: Dfa,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Strings {
+    pub strings: Vec<String>,
+}
+
+impl Output for Conversion {
+    fn to_text(&self) -> String {
+        match &self.regex {
+            Some(regex) => format!("{regex}\n"),
+            None => "none, the language is empty\n".to_string(),
+        }
+    }
+}
+
+impl Output for MethodsComparison {
+    fn to_text(&self) -> String {
+        let regex = |regex: &Option<String>| {
+            regex
+                .clone()
+                .unwrap_or_else(|| "none, the language is empty".to_string())
+        };
+        let verdict = match &self.distinguishing_word {
+            None => "regexes are equivalent".to_string(),
+            Some(word) => format!("regexes are not equivalent, they differ on '{word}'"),
+        };
+
+        format!(
+            "state elimination: {}\narden: {}\n{verdict}\n",
+            regex(&self.state_elimination),
+            regex(&self.arden)
+        )
+    }
+}
+
+impl Output for Equivalence {
+    fn to_text(&self) -> String {
+        match &self.distinguishing_word {
+            None => "equivalent\n".to_string(),
+            Some(word) => format!("not equivalent, they differ on '{word}'\n"),
+        }
+    }
+}
+
+impl Output for Minimization {
+    fn to_text(&self) -> String {
+        format!(
+            "{} states, {} in the DFA, {} in the minimal DFA\n{}",
+            self.states,
+            self.dfa_states,
+            self.dfa.size(),
+            self.dfa.to_text()
+        )
+    }
+}
+
+impl Output for Strings {
+    fn to_text(&self) -> String {
+        self.strings.iter().map(|s| format!("{s}\n")).collect()
+    }
+}
+
+impl Output for Decomposition {
+    fn to_text(&self) -> String {
+        Decomposition::to_text(self)
+    }
+}
+
+impl Output for Glushkov {
+    fn to_text(&self) -> String {
+        Glushkov::to_text(self)
+    }
+}
+
+pub fn convert(
+    convertor: &mut Convertor,
+    input: Input<'_>,
+    options: &ToRegexOptions,
+    format: OutputFormat,
+) -> Result<(), String> {
+    info!("converting {}...", input);
+    let automata = input.load(convertor)?;
+
+    output::print(
+        &Conversion {
+            input: input.to_string(),
+            states: automata.size,
+            regex: automata.to_regex_with(options),
+        },
+        format,
+    )
+}
+
+pub fn compare_methods(
+    convertor: &mut Convertor,
+    input: Input<'_>,
+    options: &ToRegexOptions,
+    format: OutputFormat,
+) -> Result<(), String> {
+    info!("comparing conversion methods for {}...", input);
+    let automata = input.load(convertor)?;
+
+    let [state_elimination, arden] = [ConversionMethod::StateElimination, ConversionMethod::Arden]
+        .map(|method| {
+            automata.to_regex_with(&ToRegexOptions {
+                dialect: Dialect::Course,
+                method,
+                ..options.clone()
+            })
+        });
+
+    let [a1, a2] = [&state_elimination, &arden].map(|regex| {
+        regex
+            .as_deref()
+            .map_or_else(Automata::new_empty, Automata::from_printed_regex)
+    });
+
+    output::print(
+        &MethodsComparison {
+            distinguishing_word: dfa::find_distinguishing_word(&a1, &a2),
+            state_elimination,
+            arden,
+        },
+        format,
+    )
+}
+
+pub fn equiv(
+    convertor: &mut Convertor,
+    inputs: [Input<'_>; 2],
+    format: OutputFormat,
+) -> Result<(), String> {
+    info!("comparing {} with {}...", inputs[0], inputs[1]);
+    let a1 = inputs[0].load(convertor)?;
+    let a2 = inputs[1].load(convertor)?;
+
+    let distinguishing_word = dfa::find_distinguishing_word(&a1, &a2);
+
+    output::print(
+        &Equivalence {
+            equivalent: distinguishing_word.is_none(),
+            distinguishing_word,
+        },
+        format,
+    )
+}
+
+pub fn minimize(
+    convertor: &mut Convertor,
+    input: Input<'_>,
+    format: OutputFormat,
+) -> Result<(), String> {
+    info!("minimizing {}...", input);
+    let automata = input.load(convertor)?;
+    let dfa = Dfa::from_automata(&automata, &dfa::get_alphabet(&[&automata]));
+
+    output::print(
+        &Minimization {
+            states: automata.size,
+            dfa_states: dfa.size(),
+            dfa: dfa.minimize(),
+        },
+        format,
+    )
+}
+
+pub fn gen_strings(
+    convertor: &mut Convertor,
+    input: Input<'_>,
+    count: usize,
+    seed: u64,
+    format: OutputFormat,
+) -> Result<(), String> {
+    info!("generating strings for {} with seed {}...", input, seed);
+    let automata = input.load(convertor)?;
+
+    output::print(
+        &Strings {
+            strings: StringGenerator::from_automata(&automata, StdRng::seed_from_u64(seed))
+                .gen_strs(count),
+        },
+        format,
+    )
+}
+
+pub fn dump_dot(r: &str, dir: &Path, options: &ToRegexOptions) -> Result<(), String> {
+    info!("dumping automatas for regex {} to {}...", r, dir.display());
+    let automata = Convertor::with_dot_dump(dir.to_path_buf()).convert(r)?;
+    automata.write_dot(dir, "automata")?;
+
+    automata.trace_elimination(options).write_dot_frames(dir)
+}
+
+pub fn export_jff(automata: &Automata, path: &Path) -> Result<(), String> {
+    info!("exporting automata to {}...", path.display());
+    automata.write_jff(path)
+}
+
+pub fn export_att(
+    automata: &Automata,
+    path: &Path,
+    symbols_path: Option<&Path>,
+) -> Result<(), String> {
+    info!("exporting automata to {}...", path.display());
+    automata.write_att(path, symbols_path)
+}
+
+pub fn render(
+    convertor: &mut Convertor,
+    input: Input<'_>,
+    format: RenderFormat,
+    output: Option<&Path>,
+) -> Result<(), String> {
+    output::write(&input.load(convertor)?.render(format), output)
+}
+
+// Text and JSON go to stdout without `output`, DOT frames need a directory
+pub fn trace(
+    convertor: &mut Convertor,
+    input: Input<'_>,
+    format: TraceFormat,
+    output: Option<&Path>,
+    options: &ToRegexOptions,
+) -> Result<(), String> {
+    let trace = input.load(convertor)?.trace_elimination(options);
+
+    let text = match format {
+        TraceFormat::Text => trace.to_text(),
+        #[cfg(feature = "serde")]
+        TraceFormat::Json => crate::ndfa::serialization::Serialization::to_json(&trace)? + "\n",
+        TraceFormat::Dot => {
+            let dir = output.ok_or("DOT frames need an output directory")?;
+            return trace.write_dot_frames(dir);
+        }
+    };
+
+    output::write(&text, output)
+}
+
+pub fn explain(convertor: &mut Convertor, r: &str, format: OutputFormat) -> Result<(), String> {
+    output::print(&convertor.explain(r)?, format)
+}
+
+// The regex is in the course notation without lookaheads, anchors are optional
+pub fn glushkov(r: &str, format: OutputFormat) -> Result<(), String> {
+    let regex = r.strip_prefix('^').unwrap_or(r);
+    let regex = regex.strip_suffix('$').unwrap_or(regex);
+
+    let tokens = parser::parse(&format!("^{regex}$"))?;
+    if tokens
+        .iter()
+        .any(|token| matches!(token, Token::LookaheadGroup(_)))
+    {
+        return Err("lookaheads have no Glushkov automata".to_string());
+    }
+
+    let glushkov = Tree::from_regex(if regex.is_empty() { "ε" } else { regex }).get_glushkov();
+
+    output::print(&glushkov, format)
+}
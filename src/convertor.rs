@@ -1,118 +1,641 @@
+use std::path::PathBuf;
+
+#[cfg(feature = "serde")]
+use crate::cache;
 use crate::{
-    ndfa::{self, Automata},
+    ndfa::{
+        self,
+        ast::Tree,
+        limits::{Budget, Limits},
+        Automata, IntersectionStats,
+    },
     parser::{parse, Token},
 };
 
 pub fn gen_rec(r: &str) -> Result<Automata, String> {
-    if r.eq("^$") {
-        return Ok(Automata::from_regex(&"".to_string()));
+    Convertor::default().convert(r)
+}
+
+/// How `Convertor` split a regex and built the automata for it.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Decomposition {
+    pub regex: String,
+    pub operation: Operation,
+    /// Of the built automata
+    pub states: usize,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Operation {
+    /// No lookaheads outside of brackets, the automata is built directly
+    Glushkov,
+    /// The automata was loaded from the cache
+    Cached,
+    /// r1 (?=lookahead) r3 => r1 · (lookahead ∩ r3)
+    Lookahead {
+        r1: String,
+        lookahead: String,
+        r3: Box<Decomposition>,
+        intersection: IntersectionStats,
+    },
+    /// r1 (r2) r3 => r1 · (r2 · r3)
+    Group {
+        r1: String,
+        r2: Box<Decomposition>,
+        r3: Box<Decomposition>,
+    },
+    /// r1 | r2 | ... => r1 ∪ r2 ∪ ..., if any of them has lookaheads
+    Union { alternatives: Vec<Decomposition> },
+    /// r1 (r2|r3) r4 => r1 · (r2 r4 | (r3) r4)
+    Alternative {
+        r1: String,
+        r2: Box<Decomposition>,
+        r3: Box<Decomposition>,
+    },
+}
+
+impl Decomposition {
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        self.write_text(0, &mut text);
+        text
     }
 
-    let tokens = parse(r)?;
-    let mut s = "".to_string();
-    let mut brackets_counter = 0;
-    let mut i = 0;
-    let mut first_bracket_idx = 0;
-    while i < tokens.len() {
-        match &tokens[i] {
-            Token::OpenBracket => {
-                brackets_counter += 1;
-                if brackets_counter == 1 {
-                    first_bracket_idx = i;
+    fn write_text(&self, depth: usize, text: &mut String) {
+        let indent = "    ".repeat(depth);
+        let operation = match self.operation {
+            Operation::Glushkov => "glushkov",
+            Operation::Cached => "cached",
+            Operation::Lookahead { .. } => "r1 · (lookahead ∩ r3)",
+            Operation::Group { .. } => "r1 · (r2 · r3)",
+            Operation::Alternative { .. } => "r1 · (r2 r4 | (r3) r4)",
+            Operation::Union { .. } => "r1 ∪ r2 ∪ ...",
+        };
+        text.push_str(&format!(
+            "{indent}{} => {} [{} states]\n",
+            self.regex, operation, self.states
+        ));
+
+        match &self.operation {
+            Operation::Glushkov | Operation::Cached => {}
+            Operation::Lookahead {
+                r1,
+                lookahead,
+                r3,
+                intersection,
+            } => {
+                text.push_str(&format!("{indent}    r1: {}\n", or_epsilon(r1)));
+                text.push_str(&format!("{indent}    lookahead: {lookahead}\n"));
+                text.push_str(&format!(
+                    "{indent}    intersection: {} states, {} after remove_traps\n",
+                    intersection.states_before_remove_traps, intersection.states_after_remove_traps
+                ));
+                r3.write_text(depth + 1, text);
+            }
+            Operation::Union { alternatives } => {
+                for alternative in alternatives {
+                    alternative.write_text(depth + 1, text);
                 }
             }
+            Operation::Group { r1, r2, r3 } | Operation::Alternative { r1, r2, r3 } => {
+                text.push_str(&format!("{indent}    r1: {}\n", or_epsilon(r1)));
+                r2.write_text(depth + 1, text);
+                r3.write_text(depth + 1, text);
+            }
+        }
+    }
+}
+
+// The alternatives outside of brackets, anchored
+fn split_alternatives(tokens: &[Token]) -> Vec<String> {
+    let mut alternatives = vec!["^".to_string()];
+    let mut brackets_counter = 0;
+
+    for token in tokens {
+        match token {
+            Token::OpenBracket => brackets_counter += 1,
             Token::CloseBracket => brackets_counter -= 1,
-            Token::LookaheadGroup(group) => {
-                if brackets_counter == 0 {
-                    let mut tmp: String = group.iter().fold("".to_string(), |acc, t| {
-                        if !matches!(t, Token::StringEnd) {
-                            acc + &t.to_string()
-                        } else {
-                            acc
-                        }
-                    });
+            Token::Binary(_) if brackets_counter == 0 => {
+                alternatives.last_mut().unwrap().push('$');
+                alternatives.push("^".to_string());
+                continue;
+            }
+            _ => {}
+        }
 
-                    if !matches!(group.last(), Some(Token::StringEnd)) {
-                        tmp += ".*";
-                    }
+        alternatives
+            .last_mut()
+            .unwrap()
+            .push_str(&token.to_string());
+    }
 
-                    let a1 = Automata::from_regex(&s);
-
-                    let a2 = Automata::from_regex(&tmp);
-
-                    let r3 = tokens[(i + 1)..]
-                        .iter()
-                        .fold("^".to_string(), |acc, t| acc + &t.to_string())
-                        + "$";
-
-                    return Ok(ndfa::concatenation(
-                        &a1,
-                        &ndfa::intersection(&a2, &gen_rec(&r3)?),
-                    ));
-                } else {
-                    let l = first_bracket_idx;
-                    let mut r = l + 1;
-                    brackets_counter = 1;
-                    let mut alternative_idx = 0;
-                    while brackets_counter != 0 {
-                        match tokens[r] {
-                            Token::OpenBracket => brackets_counter += 1,
-                            Token::CloseBracket => brackets_counter -= 1,
-                            Token::Binary(_) => {
-                                if alternative_idx == 0 && brackets_counter == 1 {
-                                    alternative_idx = r;
-                                }
+    alternatives.last_mut().unwrap().push('$');
+    alternatives
+}
+
+fn or_epsilon(regex: &str) -> &str {
+    if regex.is_empty() {
+        "ε"
+    } else {
+        regex
+    }
+}
+
+/// Builds an automata for a regex with lookaheads.
+#[derive(Debug, Clone, Default)]
+pub struct Convertor {
+    /// The automata after every intersection, concatenation and union goes there
+    dot_dump_dir: Option<PathBuf>,
+    /// Automatas of the regex and its subregexes are loaded from there if present
+    #[cfg(feature = "serde")]
+    cache_dir: Option<PathBuf>,
+    steps: usize,
+    limits: Limits,
+    // Of the last conversion
+    budget: Budget,
+    // Of the last automata built directly, the root one if the decomposition is Glushkov
+    glushkov_regex: Option<String>,
+    // Every conversion leaves its decomposition, the parent one takes them
+    decompositions: Vec<Decomposition>,
+}
+
+impl Convertor {
+    pub fn with_dot_dump(dir: PathBuf) -> Self {
+        Self {
+            dot_dump_dir: Some(dir),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn with_cache(dir: PathBuf) -> Self {
+        Self {
+            cache_dir: Some(dir),
+            ..Default::default()
+        }
+    }
+
+    /// Every conversion gets the limits anew, none by default.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Of the last conversion, so converting its automata back shares the limits.
+    pub fn budget(&self) -> &Budget {
+        &self.budget
+    }
+
+    /// Fails with `limits::ERR_RESOURCE_EXHAUSTED` if the limits are hit.
+    pub fn convert(&mut self, r: &str) -> Result<Automata, String> {
+        self.convert_in(r, self.limits.start())
+    }
+
+    /// Converts within `budget` rather than within the limits anew, so several
+    /// conversions can share it.
+    pub fn convert_in(&mut self, r: &str, budget: Budget) -> Result<Automata, String> {
+        self.decompositions.clear();
+        self.glushkov_regex = None;
+        self.budget = budget;
+        self.convert_rec(r)
+    }
+
+    /// Of the last conversion if its automata was built directly, the symbol positions
+    /// of the tree are the states then.
+    pub fn glushkov_tree(&self) -> Option<Tree> {
+        let root = self.decompositions.last()?;
+        if !matches!(root.operation, Operation::Glushkov) {
+            return None;
+        }
+
+        self.glushkov_regex
+            .as_deref()
+            .filter(|regex| !regex.is_empty())
+            .map(Tree::from_regex)
+    }
+
+    pub fn explain(&mut self, r: &str) -> Result<Decomposition, String> {
+        self.convert(r)?;
+        Ok(self.decompositions.pop().unwrap())
+    }
+
+    fn convert_rec(&mut self, r: &str) -> Result<Automata, String> {
+        #[cfg(feature = "serde")]
+        if let Some(dir) = self.cache_dir.clone() {
+            if let Some(automata) = cache::load(&dir, r) {
+                // A cached automata is as big as a built one, so it is limited alike
+                self.budget.check_matrix_size(automata.size)?;
+                self.decompose(r, Operation::Cached, &automata);
+                return Ok(automata);
+            }
+
+            let automata = self.convert_uncached(r)?;
+            if let Err(e) = cache::store(&dir, r, &automata) {
+                log::warn!("failed to cache automata for regex {}: {}", r, e);
+            }
+
+            return Ok(automata);
+        }
+
+        self.convert_uncached(r)
+    }
+
+    fn convert_uncached(&mut self, regex: &str) -> Result<Automata, String> {
+        if regex.eq("^$") {
+            let automata = Automata::from_regex("");
+            self.decompose(regex, Operation::Glushkov, &automata);
+            return Ok(automata);
+        }
+
+        let tokens = parse(regex)?;
+
+        // The split below only sees concatenations, so top-level alternatives go first
+        if tokens.iter().any(|t| matches!(t, Token::LookaheadGroup(_))) {
+            let alternatives = split_alternatives(&tokens);
+            if alternatives.len() > 1 {
+                return self.convert_alternatives(regex, &alternatives);
+            }
+        }
+
+        let mut s = "".to_string();
+        let mut brackets_counter = 0;
+        let mut i = 0;
+        let mut first_bracket_idx = 0;
+        while i < tokens.len() {
+            match &tokens[i] {
+                Token::OpenBracket => {
+                    brackets_counter += 1;
+                    if brackets_counter == 1 {
+                        first_bracket_idx = i;
+                    }
+                }
+                Token::CloseBracket => brackets_counter -= 1,
+                Token::LookaheadGroup(group) => {
+                    if brackets_counter == 0 {
+                        // The brackets keep the alternatives of the lookahead together
+                        let mut tmp: String = group.iter().fold("(".to_string(), |acc, t| {
+                            if !matches!(t, Token::StringEnd) {
+                                acc + &t.to_string()
+                            } else {
+                                acc
                             }
-                            _ => {}
+                        }) + ")";
+
+                        if !matches!(group.last(), Some(Token::StringEnd)) {
+                            tmp += ".*";
                         }
-                        r += 1;
-                    }
 
-                    r -= 1;
+                        let a1 = Automata::try_from_regex(&s, &self.budget)?;
 
-                    let r1 = tokens[0..l]
-                        .iter()
-                        .fold("".to_string(), |acc, t| acc + &t.to_string());
+                        let a2 = Automata::try_from_regex(&tmp, &self.budget)?;
 
-                    let r2 = tokens[(l + 1)..r]
-                        .iter()
-                        .fold("^".to_string(), |acc, t| acc + &t.to_string())
-                        + "$";
+                        let r3 = tokens[(i + 1)..]
+                            .iter()
+                            .fold("^".to_string(), |acc, t| acc + &t.to_string())
+                            + "$";
 
-                    let r3 = tokens[(r + 1)..]
-                        .iter()
-                        .fold("^".to_string(), |acc, t| acc + &t.to_string())
-                        + "$";
+                        let a3 = self.convert_rec(&r3)?;
+                        let (intersection, stats) =
+                            ndfa::try_intersection_with_stats(&a2, &a3, &self.budget)?;
+                        let intersection = self.record("intersection", intersection)?;
+                        let automata =
+                            self.record("concatenation", ndfa::concatenation(&a1, &intersection))?;
 
-                    if alternative_idx == 0 {
-                        return Ok(ndfa::concatenation(
-                            &Automata::from_regex(&r1),
-                            &ndfa::concatenation(&gen_rec(&r2)?, &gen_rec(&r3)?),
-                        ));
+                        let operation = Operation::Lookahead {
+                            r1: s,
+                            lookahead: tmp,
+                            r3: self.take_decomposition(),
+                            intersection: stats,
+                        };
+                        self.decompose(regex, operation, &automata);
+                        return Ok(automata);
                     } else {
-                        let r2 = tokens[(l + 1)..alternative_idx]
+                        let l = first_bracket_idx;
+                        let mut r = l + 1;
+                        brackets_counter = 1;
+                        let mut alternative_idx = 0;
+                        while brackets_counter != 0 {
+                            match tokens[r] {
+                                Token::OpenBracket => brackets_counter += 1,
+                                Token::CloseBracket => brackets_counter -= 1,
+                                Token::Binary(_)
+                                    if alternative_idx == 0 && brackets_counter == 1 =>
+                                {
+                                    alternative_idx = r;
+                                }
+                                _ => {}
+                            }
+                            r += 1;
+                        }
+
+                        r -= 1;
+
+                        let r1 = tokens[0..l]
+                            .iter()
+                            .fold("".to_string(), |acc, t| acc + &t.to_string());
+
+                        let r2 = tokens[(l + 1)..r]
                             .iter()
                             .fold("^".to_string(), |acc, t| acc + &t.to_string())
                             + "$";
 
-                        let r3 = tokens[(alternative_idx + 1)..r]
+                        let r3 = tokens[(r + 1)..]
                             .iter()
                             .fold("^".to_string(), |acc, t| acc + &t.to_string())
                             + "$";
 
-                        return Ok(ndfa::concatenation(
-                            &Automata::from_regex(&r1),
-                            &ndfa::union(&gen_rec(&r2)?, &gen_rec(&r3)?),
-                        ));
+                        if alternative_idx == 0 {
+                            let a2 = self.convert_rec(&r2)?;
+                            let a3 = self.convert_rec(&r3)?;
+                            let concatenation =
+                                self.record("concatenation", ndfa::concatenation(&a2, &a3))?;
+                            let a1 = Automata::try_from_regex(&r1, &self.budget)?;
+                            let automata = self.record(
+                                "concatenation",
+                                ndfa::concatenation(&a1, &concatenation),
+                            )?;
+
+                            let r3 = self.take_decomposition();
+                            let r2 = self.take_decomposition();
+                            self.decompose(regex, Operation::Group { r1, r2, r3 }, &automata);
+                            return Ok(automata);
+                        } else {
+                            // The rest after the group goes to both alternatives, so their
+                            // lookaheads see it; r3 may have alternatives of its own
+                            let r4 = tokens[(r + 1)..]
+                                .iter()
+                                .fold("".to_string(), |acc, t| acc + &t.to_string());
+
+                            let r2 = tokens[(l + 1)..alternative_idx]
+                                .iter()
+                                .fold("^".to_string(), |acc, t| acc + &t.to_string())
+                                + &r4
+                                + "$";
+
+                            let r3 = tokens[(alternative_idx + 1)..r]
+                                .iter()
+                                .fold("^(".to_string(), |acc, t| acc + &t.to_string())
+                                + ")"
+                                + &r4
+                                + "$";
+
+                            let a2 = self.convert_rec(&r2)?;
+                            let a3 = self.convert_rec(&r3)?;
+                            let union = self.record("union", ndfa::union(&a2, &a3))?;
+                            let a1 = Automata::try_from_regex(&r1, &self.budget)?;
+                            let automata =
+                                self.record("concatenation", ndfa::concatenation(&a1, &union))?;
+
+                            let r3 = self.take_decomposition();
+                            let r2 = self.take_decomposition();
+                            self.decompose(regex, Operation::Alternative { r1, r2, r3 }, &automata);
+                            return Ok(automata);
+                        }
                     }
                 }
+                _ => {}
             }
-            _ => {}
+
+            s += &tokens[i].to_string();
+            i += 1;
+        }
+
+        let automata = Automata::try_from_regex(&s, &self.budget)?;
+        self.decompose(regex, Operation::Glushkov, &automata);
+        self.glushkov_regex = Some(s);
+        Ok(automata)
+    }
+
+    // r1 | r2 | ... => r1 ∪ r2 ∪ ...
+    fn convert_alternatives(
+        &mut self,
+        regex: &str,
+        alternatives: &[String],
+    ) -> Result<Automata, String> {
+        let mut automata = self.convert_rec(&alternatives[0])?;
+        for alternative in &alternatives[1..] {
+            let next = self.convert_rec(alternative)?;
+            automata = self.record("union", ndfa::union(&automata, &next))?;
         }
 
-        s += &tokens[i].to_string();
-        i += 1;
+        let alternatives = self
+            .decompositions
+            .split_off(self.decompositions.len() - alternatives.len());
+        self.decompose(regex, Operation::Union { alternatives }, &automata);
+        Ok(automata)
+    }
+
+    fn decompose(&mut self, r: &str, operation: Operation, automata: &Automata) {
+        self.decompositions.push(Decomposition {
+            regex: r.to_string(),
+            operation,
+            states: automata.size,
+        });
+    }
+
+    fn take_decomposition(&mut self) -> Box<Decomposition> {
+        Box::new(self.decompositions.pop().unwrap())
+    }
+
+    fn record(&mut self, operation: &str, automata: Automata) -> Result<Automata, String> {
+        self.budget.check_time()?;
+        self.budget.check_matrix_size(automata.size)?;
+        self.steps += 1;
+
+        if let Some(dir) = &self.dot_dump_dir {
+            automata.write_dot(dir, &format!("{:03}-{}", self.steps, operation))?;
+        }
+
+        Ok(automata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gen_rec, Convertor, Operation};
+    use crate::ndfa::dfa::are_equivalent;
+    use crate::ndfa::Automata;
+
+    #[test]
+    fn glushkov() {
+        let decomposition = Convertor::default().explain("^ab*$").unwrap();
+
+        assert!(matches!(decomposition.operation, Operation::Glushkov));
+        assert_eq!(decomposition.states, 3);
+        assert_eq!(decomposition.to_text(), "^ab*$ => glushkov [3 states]\n");
+    }
+
+    #[test]
+    fn glushkov_tree() {
+        let mut convertor = Convertor::default();
+
+        convertor.convert("^ab*$").unwrap();
+        assert_eq!(convertor.glushkov_tree().unwrap().linearized_symbols, 2);
+        convertor.convert("^a(?=b)b*$").unwrap();
+        assert!(convertor.glushkov_tree().is_none());
+        convertor.convert("^$").unwrap();
+        assert!(convertor.glushkov_tree().is_none());
     }
 
-    Ok(Automata::from_regex(&s))
+    #[test]
+    fn lookahead() {
+        let decomposition = Convertor::default().explain("^a(?=b)b*$").unwrap();
+
+        match &decomposition.operation {
+            Operation::Lookahead {
+                r1,
+                lookahead,
+                r3,
+                intersection,
+            } => {
+                assert_eq!(r1, "a");
+                assert_eq!(lookahead, "(b).*");
+                assert_eq!(r3.regex, "^b*$");
+                assert_eq!(intersection.states_after_remove_traps, 3);
+            }
+            operation => panic!("{operation:?}"),
+        }
+        assert_eq!(
+            decomposition.to_text(),
+            "^a(?=b)b*$ => r1 · (lookahead ∩ r3) [4 states]\n\
+             \x20   r1: a\n\
+             \x20   lookahead: (b).*\n\
+             \x20   intersection: 3 states, 3 after remove_traps\n\
+             \x20   ^b*$ => glushkov [2 states]\n"
+        );
+    }
+
+    #[test]
+    fn group() {
+        let decomposition = Convertor::default().explain("^a((?=b)b)c$").unwrap();
+
+        match &decomposition.operation {
+            Operation::Group { r1, r2, r3 } => {
+                assert_eq!(r1, "a");
+                assert_eq!(r2.regex, "^(?=b)b$");
+                assert!(matches!(r2.operation, Operation::Lookahead { .. }));
+                assert_eq!(r3.regex, "^c$");
+            }
+            operation => panic!("{operation:?}"),
+        }
+
+        let text = decomposition.to_text();
+        assert!(text.starts_with("^a((?=b)b)c$ => r1 · (r2 · r3) [4 states]\n    r1: a\n"));
+        assert!(text.contains("\n    ^(?=b)b$ => r1 · (lookahead ∩ r3) [2 states]\n"));
+        assert!(text.ends_with("\n    ^c$ => glushkov [2 states]\n"));
+    }
+
+    #[test]
+    fn alternative() {
+        let decomposition = Convertor::default().explain("^x((?=a)a|b|c)c$").unwrap();
+
+        // The rest after the group goes to both alternatives
+        match &decomposition.operation {
+            Operation::Alternative { r1, r2, r3 } => {
+                assert_eq!(r1, "x");
+                assert_eq!(r2.regex, "^(?=a)ac$");
+                assert_eq!(r3.regex, "^(b|c)c$");
+            }
+            operation => panic!("{operation:?}"),
+        }
+
+        let text = decomposition.to_text();
+        assert!(text.starts_with("^x((?=a)a|b|c)c$ => r1 · (r2 r4 | (r3) r4)"));
+        assert!(text.contains("\n    ^(b|c)c$ => glushkov [4 states]\n"));
+    }
+
+    #[test]
+    fn alternative_rest() {
+        let mut convertor = Convertor::default();
+
+        let automata = convertor.convert("^((?=a)a|b)c$").unwrap();
+        assert!(are_equivalent(&automata, &Automata::from_regex("(a|b)c")));
+
+        let automata = convertor.convert("^x((?=a)a|b|c)c$").unwrap();
+        assert!(are_equivalent(
+            &automata,
+            &Automata::from_regex("x(a|b|c)c")
+        ));
+
+        // The lookahead sees past the group
+        let automata = convertor.convert("^(a(?=bc)|b)(b|c)c$").unwrap();
+        assert!(are_equivalent(
+            &automata,
+            &Automata::from_regex("abc|b(b|c)c")
+        ));
+    }
+
+    #[test]
+    fn top_level_alternatives() {
+        let cases = [
+            ("^a|(?=b)b$", "a|b"),
+            ("^e|x((?=x)x)$", "e|xx"),
+            ("^x(a(?=b)|c)d|e$", "xcd|e"),
+            ("^a(?=b)|$", ""),
+            ("^(?=ab|ba)(a|b)*$", "(ab|ba)(a|b)*"),
+        ];
+
+        for (regex, expected) in cases {
+            let automata = gen_rec(regex).unwrap();
+            assert!(
+                are_equivalent(&automata, &Automata::from_regex(expected)),
+                "{regex}"
+            );
+        }
+
+        let decomposition = Convertor::default().explain("^a|(?=b)b$").unwrap();
+        match &decomposition.operation {
+            Operation::Union { alternatives } => {
+                let regexes: Vec<_> = alternatives.iter().map(|a| a.regex.as_str()).collect();
+                assert_eq!(regexes, ["^a$", "^(?=b)b$"]);
+                assert!(matches!(alternatives[0].operation, Operation::Glushkov));
+            }
+            operation => panic!("{operation:?}"),
+        }
+        assert!(decomposition
+            .to_text()
+            .starts_with("^a|(?=b)b$ => r1 ∪ r2 ∪ ... [3 states]\n    ^a$ => glushkov"));
+    }
+
+    #[test]
+    fn shared_budget() {
+        use crate::ndfa::limits::{self, Limits};
+        use std::time::Duration;
+
+        let mut convertor = Convertor::default();
+        convertor.set_limits(Limits {
+            timeout: Some(Duration::from_millis(50)),
+            ..Default::default()
+        });
+        let budget = convertor.limits().start();
+        std::thread::sleep(Duration::from_millis(60));
+
+        assert!(convertor.convert("^a(?=b)b$").is_ok());
+        let e = convertor.convert_in("^a(?=b)b$", budget).unwrap_err();
+        assert!(limits::is_resource_exhausted(&e));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn cached_limits() {
+        use crate::ndfa::limits::{self, Limits};
+
+        let dir = std::env::temp_dir().join(format!("tfl-cache-{}", std::process::id()));
+        let mut convertor = Convertor::with_cache(dir.clone());
+        convertor.convert("^abc$").unwrap();
+
+        convertor.set_limits(Limits {
+            max_matrix_size: Some(3),
+            ..Default::default()
+        });
+        let result = convertor.convert("^abc$");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(limits::is_resource_exhausted(&result.unwrap_err()));
+    }
 }
@@ -0,0 +1,176 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::hash;
+
+/*
+ * A case is a file of tab-separated lines, the regex goes first:
+ *
+ * regex	^a((?=b)b|c)d$
+ * no-match	ab
+ * match	abd
+ *
+ * The verdicts are of fancy-regex on the regex with lookaheads.
+ */
+
+const REGEX_FIELD: &str = "regex";
+const MATCH_VERDICT: &str = "match";
+const NO_MATCH_VERDICT: &str = "no-match";
+const CASE_EXTENSION: &str = "case";
+
+const ERR_INVALID_CASE: &str = "invalid corpus case";
+
+/// Strings of a regex that once failed, with their expected verdicts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Case {
+    pub regex: String,
+    pub strings: Vec<(String, bool)>,
+}
+
+impl Case {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines();
+
+        let regex = match lines.next().and_then(|line| line.split_once('\t')) {
+            Some((REGEX_FIELD, regex)) => regex.to_string(),
+            _ => return Err(ERR_INVALID_CASE.to_string()),
+        };
+
+        let mut strings = Vec::new();
+        for line in lines.filter(|line| !line.is_empty()) {
+            let (verdict, string) = line.split_once('\t').ok_or(ERR_INVALID_CASE)?;
+            let is_match = match verdict {
+                MATCH_VERDICT => true,
+                NO_MATCH_VERDICT => false,
+                _ => return Err(ERR_INVALID_CASE.to_string()),
+            };
+
+            strings.push((string.to_string(), is_match));
+        }
+
+        Ok(Self { regex, strings })
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{REGEX_FIELD}\t{}\n", self.regex);
+
+        for (string, is_match) in &self.strings {
+            let verdict = if *is_match {
+                MATCH_VERDICT
+            } else {
+                NO_MATCH_VERDICT
+            };
+            text.push_str(&format!("{verdict}\t{string}\n"));
+        }
+
+        text
+    }
+}
+
+/// Cases in the order of their files.
+pub fn load(dir: &Path) -> Result<Vec<Case>, String> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().is_some_and(|e| e == CASE_EXTENSION) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+            Case::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+        })
+        .collect()
+}
+
+/// The strings are added to the stored case of the regex if there is one.
+pub fn store(dir: &Path, case: &Case) -> Result<(), String> {
+    let (path, stored) = find_path(dir, &case.regex);
+
+    let mut merged = stored.unwrap_or_else(|| Case {
+        regex: case.regex.clone(),
+        strings: Vec::new(),
+    });
+
+    for string in &case.strings {
+        if !merged.strings.contains(string) {
+            merged.strings.push(string.clone());
+        }
+    }
+
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    fs::write(path, merged.to_text()).map_err(|e| e.to_string())
+}
+
+// The file of the regex with its stored case, `<hash>-<i>.case` is taken if the
+// cases of other regexes have the same hash
+fn find_path(dir: &Path, regex: &str) -> (PathBuf, Option<Case>) {
+    let hash = hash::stable_hash(regex);
+
+    for i in 0.. {
+        let name = match i {
+            0 => format!("{hash:016x}.{CASE_EXTENSION}"),
+            i => format!("{hash:016x}-{i}.{CASE_EXTENSION}"),
+        };
+        let path = dir.join(name);
+
+        let Ok(text) = fs::read_to_string(&path) else {
+            return (path, None);
+        };
+        if let Ok(stored) = Case::parse(&text) {
+            if stored.regex == regex {
+                return (path, Some(stored));
+            }
+        }
+    }
+
+    unreachable!("the names run out after usize::MAX cases")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{find_path, load, store, Case};
+
+    #[test]
+    fn round_trip() {
+        let case = Case {
+            regex: "^a((?=b)b|c)d$".to_string(),
+            strings: vec![
+                ("ab".to_string(), false),
+                ("".to_string(), false),
+                ("abd".to_string(), true),
+            ],
+        };
+
+        assert_eq!(Case::parse(&case.to_text()).unwrap(), case);
+        assert!(Case::parse("match\tab\n").is_err());
+        assert!(Case::parse("regex\t^a$\nmaybe\ta\n").is_err());
+    }
+
+    #[test]
+    fn collision() {
+        let dir = std::env::temp_dir().join(format!("corpus-collision-{}", std::process::id()));
+        let case = |regex: &str, string: &str| Case {
+            regex: regex.to_string(),
+            strings: vec![(string.to_string(), true)],
+        };
+
+        // Another regex already took the name of ^a$
+        let (path, _) = find_path(&dir, "^a$");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, case("^b$", "b").to_text()).unwrap();
+
+        store(&dir, &case("^a$", "a")).unwrap();
+        store(&dir, &case("^a$", "a")).unwrap();
+        let cases = load(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(cases, vec![case("^a$", "a"), case("^b$", "b")]);
+    }
+}
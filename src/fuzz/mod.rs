@@ -1,3 +1,7 @@
+pub mod corpus;
 pub mod regex_generator;
+pub mod report;
 pub mod runner;
+pub mod shrinker;
 pub mod str_generator;
+pub mod triage;
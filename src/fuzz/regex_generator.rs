@@ -1,3 +1,4 @@
+use rand::rngs::StdRng;
 use rand::Rng;
 
 #[derive(Debug, Clone)]
@@ -19,22 +20,24 @@ pub struct Config {
 #[derive(Debug)]
 pub struct RegexGenerator {
     config: Config,
+    rng: StdRng,
 }
 
 impl RegexGenerator {
-    pub fn new(config: &Config) -> Self {
+    /// The same seeded `rng` gives the same regexes.
+    pub fn new(config: &Config, rng: StdRng) -> Self {
         Self {
             config: config.clone(),
+            rng,
         }
     }
 
-    pub fn generate(&self, rcount: usize) -> Vec<String> {
+    pub fn generate(&mut self, rcount: usize) -> Vec<String> {
         let mut result = vec![];
 
-        let mut rng = rand::thread_rng();
         for _ in 0..rcount {
             let mut regex;
-            let b = rng.gen_bool(0.65);
+            let b = self.rng.gen_bool(0.65);
 
             if b || self.config.max_lookahead_count < 2 || self.config.max_letter_count < 6 {
                 regex = self.generate_rec(
@@ -93,18 +96,13 @@ impl RegexGenerator {
         result
     }
 
-    fn get_random_symbol(&self) -> String {
-        let mut rng = rand::thread_rng();
-        let r = rng.gen_range(0..self.config.alphabet_size);
-        ('a'..='z')
-            .into_iter()
-            .nth(r.try_into().unwrap())
-            .unwrap()
-            .to_string()
+    fn get_random_symbol(&mut self) -> String {
+        let r = self.rng.gen_range(0..self.config.alphabet_size);
+        ('a'..='z').nth(r.try_into().unwrap()).unwrap().to_string()
     }
 
     fn generate_rec(
-        &self,
+        &mut self,
         letter_count: usize,
         star_height: usize,
         lookahead_count: usize,
@@ -113,9 +111,7 @@ impl RegexGenerator {
             return "".to_string();
         }
 
-        let mut rng = rand::thread_rng();
-
-        let r = rng.gen_range(0..5);
+        let r = self.rng.gen_range(0..5);
 
         match r {
             // concat
@@ -128,7 +124,7 @@ impl RegexGenerator {
                     lookahead_count - lookahead_count / 2,
                 );
 
-                return format!("{}{}", lhs, rhs);
+                format!("{}{}", lhs, rhs)
             }
 
             // or
@@ -146,7 +142,7 @@ impl RegexGenerator {
                     rhs = self.generate_rec(letter_count - letter_count / 2, star_height, 0);
                 }
 
-                return format!("({}|{})", lhs, rhs);
+                format!("({}|{})", lhs, rhs)
             }
 
             // star
@@ -156,10 +152,10 @@ impl RegexGenerator {
                 }
 
                 let regex = self.generate_rec(letter_count, star_height - 1, 0);
-                if regex.len() >= 1 {
-                    return format!("({})*", regex);
+                if !regex.is_empty() {
+                    format!("({})*", regex)
                 } else {
-                    return self.generate_rec(letter_count, star_height, lookahead_count);
+                    self.generate_rec(letter_count, star_height, lookahead_count)
                 }
             }
 
@@ -174,14 +170,14 @@ impl RegexGenerator {
 
                 let regex = self.generate_lookahead(letter_count);
 
-                return format!("(?={})", regex);
+                format!("(?={})", regex)
             }
         }
     }
 
     // <lookahead> ::= <lookahead><binary><lookahead> | (<lookahead>) | <lookahead><unary> | <symbol> | ε
 
-    fn generate_lookahead(&self, letter_count: usize) -> String {
+    fn generate_lookahead(&mut self, letter_count: usize) -> String {
         let mut r = "".to_string();
 
         loop {
@@ -199,21 +195,19 @@ impl RegexGenerator {
         }
     }
 
-    fn generate_lookahead_rec(&self, letter_count: usize, star_height: usize) -> String {
+    fn generate_lookahead_rec(&mut self, letter_count: usize, star_height: usize) -> String {
         if letter_count == 0 {
             return "".to_string();
         }
 
-        let mut rng = rand::thread_rng();
-
-        let r = rng.gen_range(0..4);
+        let r = self.rng.gen_range(0..4);
 
         match r {
             // concat
             0 => {
                 let lhs = self.generate_lookahead_rec(letter_count / 2, star_height);
                 let rhs = self.generate_lookahead_rec(letter_count - letter_count / 2, star_height);
-                return format!("{}{}", lhs, rhs);
+                format!("{}{}", lhs, rhs)
             }
 
             // or
@@ -230,7 +224,7 @@ impl RegexGenerator {
                     rhs = self.generate_lookahead_rec(letter_count - letter_count / 2, star_height);
                 }
 
-                return format!("({}|{})", lhs, rhs);
+                format!("({}|{})", lhs, rhs)
             }
 
             // star
@@ -241,10 +235,10 @@ impl RegexGenerator {
 
                 let r = self.generate_lookahead_rec(letter_count, star_height - 1);
 
-                if r.len() >= 1 {
-                    return format!("({})*", r);
+                if !r.is_empty() {
+                    format!("({})*", r)
                 } else {
-                    return self.generate_lookahead_rec(letter_count, star_height);
+                    self.generate_lookahead_rec(letter_count, star_height)
                 }
             }
 
@@ -0,0 +1,605 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use fancy_regex::{Error, RuntimeError};
+
+use super::triage::Stage;
+use crate::output;
+
+/// Output of the `fuzz` and `check` commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    Text,
+    #[cfg(feature = "serde")]
+    Json,
+    /// A test suite with a test case for every regex
+    Junit,
+}
+
+/// Stage of the tests a failure comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum FailureClass {
+    /// The regex is not valid for fancy-regex or the convertor
+    ParseError,
+    /// A panic while generating, converting or testing the regex
+    ConversionPanic,
+    /// A limit of the conversion was hit
+    Timeout,
+    /// fancy-regex does not accept the printed regex
+    InvalidPrintedRegex,
+    /// The course notation regex read back describes another language than the automata
+    RoundTrip,
+    /// fancy-regex ran out of its backtrack limit on a string with one of the regexes
+    BacktrackLimit(Side),
+    /// fancy-regex ran out of its stack on a string with one of the regexes
+    StackOverflow(Side),
+    /// Any other error of fancy-regex on a string with one of the regexes
+    EngineError(Side),
+    /// The regexes disagree on a string
+    VerdictMismatch,
+}
+
+impl FailureClass {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FailureClass::ParseError => "parse error",
+            FailureClass::ConversionPanic => "conversion panic",
+            FailureClass::Timeout => "timeout",
+            FailureClass::InvalidPrintedRegex => "invalid printed regex",
+            FailureClass::RoundTrip => "round trip",
+            FailureClass::BacktrackLimit(Side::Input) => "backtrack limit of the input regex",
+            FailureClass::BacktrackLimit(Side::Generated) => {
+                "backtrack limit of the generated regex"
+            }
+            FailureClass::StackOverflow(Side::Input) => "stack overflow of the input regex",
+            FailureClass::StackOverflow(Side::Generated) => "stack overflow of the generated regex",
+            FailureClass::EngineError(Side::Input) => "engine error of the input regex",
+            FailureClass::EngineError(Side::Generated) => "engine error of the generated regex",
+            FailureClass::VerdictMismatch => "verdict mismatch",
+        }
+    }
+}
+
+/// Regex a string was matched with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Side {
+    /// The regex with lookaheads
+    Input,
+    /// The regex converted from the automata
+    Generated,
+}
+
+/// Why a regex was not tested on its strings.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RegexError {
+    pub class: FailureClass,
+    pub message: String,
+}
+
+/// Why fancy-regex gave no verdict on a string.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StringError {
+    pub class: FailureClass,
+    pub message: String,
+}
+
+impl StringError {
+    pub fn new(side: Side, error: &Error) -> StringError {
+        let class = match error {
+            Error::RuntimeError(RuntimeError::BacktrackLimitExceeded) => {
+                FailureClass::BacktrackLimit(side)
+            }
+            Error::RuntimeError(RuntimeError::StackOverflow) => FailureClass::StackOverflow(side),
+            _ => FailureClass::EngineError(side),
+        };
+
+        StringError {
+            class,
+            message: error.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FuzzReport {
+    pub regexes: Vec<RegexReport>,
+}
+
+/// Outcome of the tests for one regex.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RegexReport {
+    pub regex: String,
+    /// Of the regex generator and of the string generator, None for the corpus cases
+    pub seed: Option<u64>,
+    /// In the dialect of the options, None for the empty language
+    pub result: Option<String>,
+    pub strings: Vec<StringReport>,
+    /// The strings are not tested then
+    pub error: Option<RegexError>,
+    /// The shortest word of exactly one of the automata and its regex read back, if the
+    /// round trip was checked and failed
+    pub round_trip_word: Option<String>,
+    /// The simplest regex the regexes still disagree on, if the regex failed and was shrunk
+    pub minimal_regex: Option<String>,
+    /// The shortest string the regexes disagree on, if the regex failed and was shrunk
+    pub minimal_string: Option<String>,
+    /// Building the automata and converting it back to a regex
+    pub conversion_seconds: f64,
+    pub matching_seconds: f64,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StringReport {
+    pub string: String,
+    /// Whether the regex with lookaheads matches the string, None on errors
+    pub expected: Option<bool>,
+    /// Whether the generated regex matches the string, None on errors
+    pub actual: Option<bool>,
+    /// Of the regex engine, with the input regex if both regexes failed
+    pub error: Option<StringError>,
+    /// Why the automata accepts or rejects the string if the verdicts differ
+    pub witness: Option<String>,
+    /// The first stage of the conversion that changed the verdict, if the verdicts differ
+    pub fault: Option<Stage>,
+}
+
+impl StringReport {
+    pub fn is_failed(&self) -> bool {
+        matches!((self.expected, self.actual), (Some(e), Some(a)) if e != a)
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.error.is_some()
+    }
+
+    fn to_text(&self) -> String {
+        let verdict = |is_match: Option<bool>| match is_match {
+            Some(true) => "match",
+            Some(false) => "no match",
+            None => "error",
+        };
+
+        match (&self.error, &self.witness) {
+            (Some(error), _) => format!(
+                "'{}': {}: {}",
+                self.string,
+                error.class.name(),
+                error.message
+            ),
+            (None, Some(witness)) => format!(
+                "'{}': expected {}, got {}, automata {}{}",
+                self.string,
+                verdict(self.expected),
+                verdict(self.actual),
+                witness,
+                self.fault
+                    .map_or_else(String::new, |fault| format!(", at fault: {}", fault.name()))
+            ),
+            (None, None) => format!("'{}': {}", self.string, verdict(self.expected)),
+        }
+    }
+}
+
+impl RegexReport {
+    pub fn failures_count(&self) -> usize {
+        self.strings.iter().filter(|s| s.is_failed()).count()
+    }
+
+    pub fn errors_count(&self) -> usize {
+        self.strings.iter().filter(|s| s.is_error()).count()
+    }
+
+    pub fn is_passed(&self) -> bool {
+        self.failures().is_empty()
+    }
+
+    /// Classes of the regex error and of the failed strings, one for each of them.
+    pub fn failures(&self) -> Vec<FailureClass> {
+        let strings = self.strings.iter().filter_map(|s| match &s.error {
+            Some(error) => Some(error.class),
+            None => s.is_failed().then_some(FailureClass::VerdictMismatch),
+        });
+
+        let round_trip = self.round_trip_word.iter().map(|_| FailureClass::RoundTrip);
+
+        self.error
+            .iter()
+            .map(|e| e.class)
+            .chain(round_trip)
+            .chain(strings)
+            .collect()
+    }
+
+    pub fn seconds(&self) -> f64 {
+        self.conversion_seconds + self.matching_seconds
+    }
+
+    fn to_text(&self) -> String {
+        let status = if matches!(&self.error, Some(e) if e.class == FailureClass::Timeout) {
+            "TIMEOUT"
+        } else if self.is_passed() {
+            "ok"
+        } else {
+            "FAILED"
+        };
+        let result = self.result.as_deref().unwrap_or(if self.error.is_some() {
+            "none, the conversion failed"
+        } else {
+            "none, the language is empty"
+        });
+
+        let seed = self
+            .seed
+            .map_or_else(String::new, |seed| format!("seed {seed}, "));
+
+        let mut text = format!(
+            "{status:<8}{} => {} ({seed}{} strings, {} failed, {} errors, {:.3} s)\n",
+            self.regex,
+            result,
+            self.strings.len(),
+            self.failures_count(),
+            self.errors_count(),
+            self.seconds()
+        );
+
+        if let Some(error) = &self.error {
+            text.push_str(&format!(
+                "        {}: {}\n",
+                error.class.name(),
+                error.message
+            ));
+        }
+
+        if let Some(word) = &self.round_trip_word {
+            text.push_str(&format!(
+                "        round trip: '{word}' is in exactly one of the automata and its regex\n"
+            ));
+        }
+
+        if let Some(minimal_regex) = &self.minimal_regex {
+            text.push_str(&format!("        minimal failing regex: {minimal_regex}\n"));
+        }
+
+        if let Some(minimal_string) = &self.minimal_string {
+            text.push_str(&format!(
+                "        minimal failing string: '{minimal_string}'\n"
+            ));
+        }
+
+        for string in self
+            .strings
+            .iter()
+            .filter(|s| s.is_failed() || s.is_error())
+        {
+            text.push_str(&format!("        {}\n", string.to_text()));
+        }
+
+        text
+    }
+}
+
+impl FuzzReport {
+    pub fn failed_count(&self) -> usize {
+        self.regexes.iter().filter(|r| !r.is_passed()).count()
+    }
+
+    pub fn is_passed(&self) -> bool {
+        self.failed_count() == 0
+    }
+
+    pub fn seconds(&self) -> f64 {
+        self.regexes.iter().map(RegexReport::seconds).sum()
+    }
+
+    /// Failures are the strings the regexes disagree on and the failed round trips,
+    /// errors are of the regex engine, panics and timeouts.
+    pub fn to_junit(&self) -> String {
+        let failures: usize = self
+            .regexes
+            .iter()
+            .map(|r| r.failures_count() + usize::from(r.round_trip_word.is_some()))
+            .sum();
+        let errors: usize = self
+            .regexes
+            .iter()
+            .map(|r| r.errors_count() + usize::from(r.error.is_some()))
+            .sum();
+
+        let mut junit = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <testsuites>\n\
+             \t<testsuite name=\"fuzz\" tests=\"{}\" failures=\"{failures}\" errors=\"{errors}\" time=\"{:.3}\">\n",
+            self.regexes.len(),
+            self.seconds()
+        );
+
+        for report in &self.regexes {
+            junit.push_str(&format!(
+                "\t\t<testcase classname=\"fuzz\" name=\"{}\" time=\"{:.3}\">\n",
+                escape_xml(&report.regex),
+                report.seconds()
+            ));
+            junit.push_str("\t\t\t<properties>\n");
+            if let Some(seed) = report.seed {
+                junit.push_str(&format!(
+                    "\t\t\t\t<property name=\"seed\" value=\"{seed}\"/>\n"
+                ));
+            }
+            if let Some(minimal_regex) = &report.minimal_regex {
+                junit.push_str(&format!(
+                    "\t\t\t\t<property name=\"minimal_regex\" value=\"{}\"/>\n",
+                    escape_xml(minimal_regex)
+                ));
+            }
+            if let Some(minimal_string) = &report.minimal_string {
+                junit.push_str(&format!(
+                    "\t\t\t\t<property name=\"minimal_string\" value=\"{}\"/>\n",
+                    escape_xml(minimal_string)
+                ));
+            }
+            junit.push_str("\t\t\t</properties>\n");
+
+            if let Some(error) = &report.error {
+                junit.push_str(&format!(
+                    "\t\t\t<error type=\"{}\" message=\"{}\"/>\n",
+                    error.class.name(),
+                    escape_xml(&error.message)
+                ));
+            }
+
+            if let Some(word) = &report.round_trip_word {
+                junit.push_str(&format!(
+                    "\t\t\t<failure type=\"{}\" message=\"'{}' is in exactly one of the automata and its regex\"/>\n",
+                    FailureClass::RoundTrip.name(),
+                    escape_xml(word)
+                ));
+            }
+
+            for string in &report.strings {
+                let (tag, class) = if let Some(error) = &string.error {
+                    ("error", error.class)
+                } else if string.is_failed() {
+                    ("failure", FailureClass::VerdictMismatch)
+                } else {
+                    continue;
+                };
+
+                junit.push_str(&format!(
+                    "\t\t\t<{tag} type=\"{}\" message=\"{}\"/>\n",
+                    class.name(),
+                    escape_xml(&string.to_text())
+                ));
+            }
+
+            junit.push_str("\t\t</testcase>\n");
+        }
+
+        junit.push_str("\t</testsuite>\n</testsuites>\n");
+        junit
+    }
+
+    /// Failures of all the regexes by class.
+    pub fn failure_counts(&self) -> BTreeMap<FailureClass, usize> {
+        let mut counts = BTreeMap::new();
+        for class in self.regexes.iter().flat_map(RegexReport::failures) {
+            *counts.entry(class).or_insert(0) += 1;
+        }
+
+        counts
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text: String = self.regexes.iter().map(RegexReport::to_text).collect();
+        text.push_str(&format!(
+            "{} regexes, {} failed, {:.3} s\n",
+            self.regexes.len(),
+            self.failed_count(),
+            self.seconds()
+        ));
+
+        for (class, count) in self.failure_counts() {
+            text.push_str(&format!("    {}: {count}\n", class.name()));
+        }
+
+        text
+    }
+
+    /// Prints the report to stdout if no file is given.
+    pub fn write(&self, format: ReportFormat, path: Option<&Path>) -> Result<(), String> {
+        let text = match format {
+            ReportFormat::Text => self.to_text(),
+            #[cfg(feature = "serde")]
+            ReportFormat::Json => serde_json::to_string(self).map_err(|e| e.to_string())? + "\n",
+            ReportFormat::Junit => self.to_junit(),
+        };
+
+        output::write(&text, path)
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.chars()
+        .map(|symbol| match symbol {
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '&' => "&amp;".to_string(),
+            '"' => "&quot;".to_string(),
+            '\n' => "&#10;".to_string(),
+            symbol => symbol.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use fancy_regex::{CompileError, Error, RuntimeError};
+
+    use super::{
+        FailureClass, FuzzReport, RegexError, RegexReport, Side, Stage, StringError, StringReport,
+    };
+
+    fn string_report(string: &str, expected: bool, actual: bool) -> StringReport {
+        StringReport {
+            string: string.to_string(),
+            expected: Some(expected),
+            actual: Some(actual),
+            error: None,
+            witness: (expected != actual).then(|| "rejected after 'a'".to_string()),
+            fault: (expected != actual).then_some(Stage::ToRegex),
+        }
+    }
+
+    #[test]
+    fn junit() {
+        let report = FuzzReport {
+            regexes: vec![
+                RegexReport {
+                    regex: "^a(?=b)b*$".to_string(),
+                    seed: Some(1),
+                    result: Some("^abb*$".to_string()),
+                    strings: vec![string_report("ab", true, true)],
+                    error: None,
+                    round_trip_word: None,
+                    minimal_regex: None,
+                    minimal_string: None,
+                    conversion_seconds: 0.5,
+                    matching_seconds: 0.25,
+                },
+                RegexReport {
+                    regex: "^a(?=\"<&)$".to_string(),
+                    seed: None,
+                    result: None,
+                    strings: vec![string_report("ab", true, false)],
+                    error: None,
+                    round_trip_word: None,
+                    minimal_regex: Some("^(?=\"<&)$".to_string()),
+                    minimal_string: Some("ab".to_string()),
+                    conversion_seconds: 0.0,
+                    matching_seconds: 0.0,
+                },
+                RegexReport {
+                    regex: "^(a$".to_string(),
+                    error: Some(RegexError {
+                        class: FailureClass::ParseError,
+                        message: "unclosed bracket".to_string(),
+                    }),
+                    ..Default::default()
+                },
+            ],
+        };
+        assert!(!report.is_passed());
+        assert_eq!(report.failed_count(), 2);
+        assert_eq!(
+            report.failure_counts().into_iter().collect::<Vec<_>>(),
+            vec![
+                (FailureClass::ParseError, 1),
+                (FailureClass::VerdictMismatch, 1)
+            ]
+        );
+
+        let junit = report.to_junit();
+        let document = roxmltree::Document::parse(&junit).unwrap();
+        let suite = document
+            .descendants()
+            .find(|n| n.has_tag_name("testsuite"))
+            .unwrap();
+
+        assert_eq!(suite.attribute("tests"), Some("3"));
+        assert_eq!(suite.attribute("failures"), Some("1"));
+        assert_eq!(suite.attribute("errors"), Some("1"));
+        assert_eq!(suite.attribute("time"), Some("0.750"));
+
+        let cases: Vec<_> = suite
+            .children()
+            .filter(|n| n.has_tag_name("testcase"))
+            .collect();
+        assert_eq!(cases[1].attribute("name"), Some("^a(?=\"<&)$"));
+        assert!(cases[0].children().all(|n| !n.has_tag_name("failure")));
+        assert!(cases[1].children().any(|n| n.has_tag_name("failure")
+            && n.attribute("message")
+                .unwrap()
+                .ends_with("at fault: to_regex")));
+        assert!(cases[1]
+            .descendants()
+            .any(|n| n.attribute("value") == Some("^(?=\"<&)$")));
+        assert!(cases[2]
+            .children()
+            .any(|n| n.has_tag_name("error") && n.attribute("type") == Some("parse error")));
+    }
+
+    #[test]
+    fn string_errors() {
+        let cases = [
+            (
+                Side::Input,
+                Error::RuntimeError(RuntimeError::BacktrackLimitExceeded),
+                FailureClass::BacktrackLimit(Side::Input),
+            ),
+            (
+                Side::Generated,
+                Error::RuntimeError(RuntimeError::BacktrackLimitExceeded),
+                FailureClass::BacktrackLimit(Side::Generated),
+            ),
+            (
+                Side::Input,
+                Error::RuntimeError(RuntimeError::StackOverflow),
+                FailureClass::StackOverflow(Side::Input),
+            ),
+            (
+                Side::Generated,
+                Error::RuntimeError(RuntimeError::StackOverflow),
+                FailureClass::StackOverflow(Side::Generated),
+            ),
+            (
+                Side::Input,
+                Error::CompileError(CompileError::LookBehindNotConst),
+                FailureClass::EngineError(Side::Input),
+            ),
+            (
+                Side::Generated,
+                Error::CompileError(CompileError::LookBehindNotConst),
+                FailureClass::EngineError(Side::Generated),
+            ),
+        ];
+
+        for (side, error, class) in cases {
+            let report = RegexReport {
+                regex: "^a*$".to_string(),
+                strings: vec![
+                    StringReport {
+                        error: Some(StringError::new(side, &error)),
+                        expected: None,
+                        actual: None,
+                        ..string_report("aa", true, true)
+                    },
+                    string_report("a", true, true),
+                ],
+                ..Default::default()
+            };
+            assert_eq!(report.failures(), vec![class]);
+
+            let junit = FuzzReport {
+                regexes: vec![report],
+            }
+            .to_junit();
+            let document = roxmltree::Document::parse(&junit).unwrap();
+            let errors: Vec<_> = document
+                .descendants()
+                .filter(|n| n.has_tag_name("error"))
+                .collect();
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].attribute("type"), Some(class.name()));
+            assert!(errors[0]
+                .attribute("message")
+                .unwrap()
+                .contains(&error.to_string()));
+        }
+    }
+}
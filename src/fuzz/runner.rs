@@ -1,63 +1,732 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+use crate::convertor::Convertor;
 use crate::fuzz::str_generator;
+use crate::hash;
+use crate::ndfa::ast::Tree;
+use crate::ndfa::limits::{self, Budget};
+use crate::ndfa::printer::Dialect;
+use crate::ndfa::{dfa, Automata, ToRegexOptions};
 
+use super::corpus::{self, Case};
 use super::regex_generator::{self, RegexGenerator};
+use super::report::{
+    FailureClass, FuzzReport, RegexError, RegexReport, Side, StringError, StringReport,
+};
+use super::shrinker;
+use super::triage::Triage;
 use fancy_regex::Regex;
 use log::{error, info};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// What is done for a regex besides testing it on the strings.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Checks {
+    /// Look for the minimal failing regex and string
+    pub shrink: bool,
+    /// Check that the course notation regex of the automata read back describes it
+    pub round_trip: bool,
+}
+
+/// Seeds of the regexes of a run, output `i` of splitmix64 is the seed of regex `i`.
+pub fn get_regex_seeds(seed: u64, regex_count: usize) -> Vec<u64> {
+    (0..regex_count as u64)
+        .map(|i| hash::splitmix64(seed, i))
+        .collect()
+}
+
+/// Every regex is generated and tested with its own seed, so `--regex-seed` replays
+/// it alone. The regexes are spread over `jobs` workers with their own convertors,
+/// the report keeps the order of the seeds.
+pub fn run_tests(
+    convertor: &Convertor,
+    seeds: &[u64],
+    strs_count: usize,
+    cfg: &regex_generator::Config,
+    options: &ToRegexOptions,
+    checks: Checks,
+    jobs: usize,
+) -> FuzzReport {
+    let regex_count = seeds.len();
+    let next = AtomicUsize::new(0);
+    let reports = Mutex::new(vec![None; regex_count]);
 
-pub fn run_tests(regex_count: usize, strs_count: usize, cfg: &regex_generator::Config) {
-    let generator = RegexGenerator::new(&cfg);
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, regex_count.max(1)) {
+            let mut convertor = convertor.clone();
+            let (next, reports) = (&next, &reports);
 
-    let regexes = generator.generate(regex_count);
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= regex_count {
+                    break;
+                }
 
-    for r in regexes {
-        run_tests_for_regex(&r, strs_count)
+                // A panic of the generator is reported for the seed, with no regex
+                let regex_seed = seeds[i];
+                let report = run_isolated("", Some(regex_seed), || {
+                    let mut generator = RegexGenerator::new(cfg, StdRng::seed_from_u64(regex_seed));
+                    let r = generator.generate(1).pop().unwrap();
+                    run_tests_for_regex(&mut convertor, &r, strs_count, options, regex_seed, checks)
+                });
+
+                reports.lock().unwrap()[i] = Some(report);
+            });
+        }
+    });
+
+    FuzzReport {
+        regexes: reports
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect(),
     }
 }
 
-pub fn run_tests_for_regex(r: &str, strs_count: usize) {
-    info!("starting tests for regex {}...", r);
+/// Every failure of the regex is classified in the report, panics are caught too.
+pub fn run_tests_for_regex(
+    convertor: &mut Convertor,
+    r: &str,
+    strs_count: usize,
+    options: &ToRegexOptions,
+    seed: u64,
+    checks: Checks,
+) -> RegexReport {
+    run_isolated(r, Some(seed), || {
+        test_regex(convertor, r, strs_count, options, seed, checks)
+    })
+}
+
+// A panic in `test` fails the regex alone, the other regexes go on
+fn run_isolated(r: &str, seed: Option<u64>, test: impl FnOnce() -> RegexReport) -> RegexReport {
+    panic::catch_unwind(AssertUnwindSafe(test)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        error!("tests for regex {} panicked: {}", r, message);
+
+        RegexReport {
+            regex: r.to_string(),
+            seed,
+            error: Some(RegexError {
+                class: FailureClass::ConversionPanic,
+                message,
+            }),
+            ..Default::default()
+        }
+    })
+}
+
+fn test_regex(
+    convertor: &mut Convertor,
+    r: &str,
+    strs_count: usize,
+    options: &ToRegexOptions,
+    seed: u64,
+    checks: Checks,
+) -> RegexReport {
+    info!("starting tests for regex {} with seed {}...", r, seed);
+    let mut report = RegexReport {
+        regex: r.to_string(),
+        seed: Some(seed),
+        ..Default::default()
+    };
+
+    let with_lookahead = match Regex::new(r) {
+        Ok(with_lookahead) => with_lookahead,
+        Err(e) => {
+            error!("regex {} is not valid: {}", r, e);
+            report.error = Some(RegexError {
+                class: FailureClass::ParseError,
+                message: e.to_string(),
+            });
+            return report;
+        }
+    };
+
     info!("creating automata...");
-    let automata = crate::convertor::gen_rec(&r).unwrap();
-    info!(
-        "generated regex: {}",
-        automata
-            .to_regex()
-            .unwrap_or_else(|| return "^$".to_string())
+    let conversion_start = Instant::now();
+    let converted = convert(convertor, r, options, convertor.limits().start());
+    report.conversion_seconds = conversion_start.elapsed().as_secs_f64();
+
+    let conversion = match converted {
+        Ok(conversion) => conversion,
+        Err(e) => {
+            error!(
+                "conversion of regex {} failed: {}: {}",
+                r,
+                e.class.name(),
+                e.message
+            );
+            report.error = Some(e);
+            return report;
+        }
+    };
+    report.result = conversion.result.clone();
+    match &report.result {
+        Some(regex) => info!("generated regex: {}", regex),
+        None => info!("generated regex: none, the language is empty"),
+    }
+
+    let mut str_gen = str_generator::StringGenerator::from_automata(
+        &conversion.automata,
+        StdRng::seed_from_u64(seed),
     );
-    let mut str_gen = str_generator::StringGenerator::from_automata(&automata);
     info!("generating strings...");
     let strs = str_gen.gen_strs(strs_count);
     info!("running tests...");
-    let mut regex = "".to_string();
-    automata
-        .to_regex()
-        .unwrap_or_else(|| "^$".to_string())
-        .chars()
-        .for_each(|c| match c {
-            'ε' => regex.push_str("(.?)"),
-            _ => regex.push(c),
+    let matching_start = Instant::now();
+    let strs = strs
+        .into_iter()
+        .map(|str| {
+            let expected = with_lookahead.is_match(&str);
+            (str, expected)
+        })
+        .collect();
+    report.strings = test_strings(&conversion, strs);
+    report.matching_seconds = matching_start.elapsed().as_secs_f64();
+
+    let failed_strings: Vec<String> = report
+        .strings
+        .iter()
+        .filter(|s| s.is_failed())
+        .map(|s| s.string.clone())
+        .collect();
+
+    // The shrinking converts again, so the budget of this conversion is kept
+    let budget = convertor.budget().clone();
+    let printed =
+        (checks.round_trip || !failed_strings.is_empty()).then(|| read_back(&conversion, &budget));
+    let find_round_trip_word = || match &printed {
+        Some(Ok(printed)) => {
+            dfa::try_find_distinguishing_word(&conversion.automata, printed, &budget)
+        }
+        Some(Err(e)) => Err(e.clone()),
+        None => Ok(None),
+    };
+
+    if checks.round_trip {
+        match find_round_trip_word() {
+            Ok(word) => report.round_trip_word = word,
+            Err(e) => {
+                error!("round trip of regex {} failed: {}", r, e);
+                report.error = Some(get_conversion_error(e));
+                return report;
+            }
+        }
+        if let Some(word) = &report.round_trip_word {
+            error!("\t round trip failed with word: '{}'", word);
+        }
+    }
+
+    if let Some(printed) = &printed {
+        localize_faults(&mut report.strings, &conversion, printed);
+    }
+
+    // A candidate for the minimal string, looked for before the budget runs out
+    let round_trip_word = if checks.round_trip {
+        report.round_trip_word.clone()
+    } else if checks.shrink && !failed_strings.is_empty() {
+        find_round_trip_word().unwrap_or_else(|e| {
+            info!("no round trip word for regex {}: {}", r, e);
+            None
+        })
+    } else {
+        None
+    };
+
+    report.minimal_regex = (checks.shrink && !failed_strings.is_empty()).then(|| {
+        info!("shrinking regex {}...", r);
+        // The candidates share the limits of one conversion, so they can not run on
+        // for every attempt
+        let shrink_budget = convertor.limits().start();
+        let minimal_regex = shrinker::shrink(r, |candidate| {
+            is_failing(
+                convertor,
+                candidate,
+                &failed_strings,
+                strs_count,
+                options,
+                seed,
+                &shrink_budget,
+            )
+        });
+        info!("minimal failing regex: {}", minimal_regex);
+        minimal_regex
+    });
+    report.minimal_string = failed_strings
+        .iter()
+        .min_by_key(|str| str.chars().count())
+        .filter(|_| checks.shrink)
+        .map(|str| {
+            info!("shrinking string '{}'...", str);
+            let minimal_string = shrink_string(
+                &conversion,
+                &with_lookahead,
+                round_trip_word.as_deref(),
+                str,
+            );
+            info!("minimal failing string: '{}'", minimal_string);
+            minimal_string
         });
-    let with_lookahead = Regex::new(&r).unwrap();
-    let without_lookahead = Regex::new(&regex).unwrap();
-    for str in strs {
-        let lhs = with_lookahead.is_match(&str);
-        let rhs = without_lookahead.is_match(&str);
-        if let Err(e) = lhs {
-            error!("got err: {}", e);
+
+    report
+}
+
+/// Reruns the cases of the corpus in `dir` against their stored verdicts.
+pub fn replay(
+    convertor: &mut Convertor,
+    dir: &Path,
+    options: &ToRegexOptions,
+) -> Result<FuzzReport, String> {
+    let cases = corpus::load(dir)?;
+
+    Ok(FuzzReport {
+        regexes: cases
+            .iter()
+            .map(|case| run_isolated(&case.regex, None, || replay_case(convertor, case, options)))
+            .collect(),
+    })
+}
+
+/// Adds the failed strings of the failed regexes to the corpus in `dir`, and the
+/// minimal failing regexes with the strings they fail on. A regex without such
+/// strings is skipped, and a case that can not be stored is logged.
+pub fn store_failures(
+    convertor: &mut Convertor,
+    dir: &Path,
+    report: &FuzzReport,
+    strs_count: usize,
+    options: &ToRegexOptions,
+) {
+    for regex in report.regexes.iter().filter(|r| !r.is_passed()) {
+        let mut strings: Vec<String> = regex
+            .strings
+            .iter()
+            .filter(|s| s.is_failed())
+            .map(|s| s.string.clone())
+            .collect();
+        strings.extend(regex.minimal_string.clone());
+        if strings.is_empty() {
             continue;
         }
+        store_case(dir, &regex.regex, &strings);
 
-        if let Err(e) = rhs {
-            error!("got err: {}", e);
-            continue;
+        if let Some(minimal_regex) = &regex.minimal_regex {
+            // The minimal regex was shrunk on the same strings and seed
+            let strings = find_failed_strings(
+                convertor,
+                minimal_regex,
+                &strings,
+                strs_count,
+                options,
+                regex.seed.unwrap_or_default(),
+                &convertor.limits().start(),
+            );
+            store_case(dir, minimal_regex, &strings);
+        }
+    }
+}
+
+// The verdicts of the engine can only be stored for the strings it handles
+fn store_case(dir: &Path, r: &str, strings: &[String]) {
+    let stored = Regex::new(r)
+        .map_err(|e| e.to_string())
+        .and_then(|with_lookahead| {
+            let strings: Vec<(String, bool)> = strings
+                .iter()
+                .filter_map(|str| Some((str.clone(), with_lookahead.is_match(str).ok()?)))
+                .collect();
+            if strings.is_empty() {
+                return Ok(());
+            }
+
+            corpus::store(
+                dir,
+                &Case {
+                    regex: r.to_string(),
+                    strings,
+                },
+            )
+        });
+
+    if let Err(e) = stored {
+        error!("failed to store regex {} in the corpus: {}", r, e);
+    }
+}
+
+fn replay_case(convertor: &mut Convertor, case: &Case, options: &ToRegexOptions) -> RegexReport {
+    info!("replaying regex {}...", case.regex);
+    let mut report = RegexReport {
+        regex: case.regex.clone(),
+        ..Default::default()
+    };
+
+    let conversion_start = Instant::now();
+    let converted = convert(convertor, &case.regex, options, convertor.limits().start());
+    report.conversion_seconds = conversion_start.elapsed().as_secs_f64();
+
+    let conversion = match converted {
+        Ok(conversion) => conversion,
+        Err(e) => {
+            error!(
+                "conversion of regex {} failed: {}: {}",
+                case.regex,
+                e.class.name(),
+                e.message
+            );
+            report.error = Some(e);
+            return report;
         }
+    };
+    report.result = conversion.result.clone();
+
+    let matching_start = Instant::now();
+    let strs = case
+        .strings
+        .iter()
+        .map(|(str, expected)| (str.clone(), Ok(*expected)))
+        .collect();
+    report.strings = test_strings(&conversion, strs);
+    report.matching_seconds = matching_start.elapsed().as_secs_f64();
+
+    if report.strings.iter().any(StringReport::is_failed) {
+        let printed = read_back(&conversion, convertor.budget());
+        localize_faults(&mut report.strings, &conversion, &printed);
+    }
+
+    report
+}
+
+// The automata of a regex with the regexes printed for it
+struct Conversion {
+    automata: Automata,
+    /// In the dialect of the options
+    result: Option<String>,
+    course_regex: Option<String>,
+    without_lookahead: Option<Regex>,
+    /// If the automata was built directly, for the witnesses
+    glushkov_tree: Option<Tree>,
+}
 
-        let lhs = lhs.unwrap();
-        let rhs = rhs.unwrap();
-        if lhs != rhs {
-            error!("\t failed with string: '{}'", str);
+// The automata is converted back once and printed in every dialect needed
+fn convert(
+    convertor: &mut Convertor,
+    r: &str,
+    options: &ToRegexOptions,
+    budget: Budget,
+) -> Result<Conversion, RegexError> {
+    let automata = convertor
+        .convert_in(r, budget)
+        .map_err(get_conversion_error)?;
+    let tree = automata
+        .try_to_regex_tree(options, convertor.budget())
+        .map_err(get_conversion_error)?;
+    let print = |dialect: Dialect| tree.as_ref().map(|tree| dialect.print_anchored(tree));
+
+    Ok(Conversion {
+        result: print(options.dialect),
+        course_regex: print(Dialect::Course),
+        without_lookahead: get_pcre_regex(print(Dialect::Pcre))?,
+        glushkov_tree: convertor.glushkov_tree(),
+        automata,
+    })
+}
+
+// The convertor fails on the regexes it can not parse, unless a limit is hit
+fn get_conversion_error(message: String) -> RegexError {
+    let class = if limits::is_resource_exhausted(&message) {
+        FailureClass::Timeout
+    } else {
+        FailureClass::ParseError
+    };
+
+    RegexError { class, message }
+}
+
+// The course regex read back into an automata, the empty one for the empty language
+fn read_back(conversion: &Conversion, budget: &Budget) -> Result<Automata, String> {
+    match &conversion.course_regex {
+        Some(regex) => Automata::try_from_printed_regex(regex, budget),
+        None => Ok(Automata::new_empty()),
+    }
+}
+
+// Matches the strings with the generated regex and compares with the expected verdicts
+fn test_strings(
+    conversion: &Conversion,
+    strs: Vec<(String, Result<bool, fancy_regex::Error>)>,
+) -> Vec<StringReport> {
+    let mut strings = Vec::new();
+    for (str, lhs) in strs {
+        let rhs = is_match(conversion.without_lookahead.as_ref(), &str);
+
+        let mut report = StringReport {
+            expected: lhs.as_ref().ok().copied(),
+            actual: rhs.as_ref().ok().copied(),
+            error: lhs
+                .err()
+                .map(|e| StringError::new(Side::Input, &e))
+                .or_else(|| rhs.err().map(|e| StringError::new(Side::Generated, &e))),
+            witness: None,
+            fault: None,
+            string: str,
+        };
+
+        if let Some(e) = &report.error {
+            error!("got err: {}: {}", e.class.name(), e.message);
+        } else if report.is_failed() {
+            let witness = conversion
+                .automata
+                .find_witness(&report.string)
+                .describe(&report.string, conversion.glushkov_tree.as_ref());
+            error!("\t failed with string: '{}'", report.string);
+            error!("\t automata: {}", witness);
+            report.witness = Some(witness);
         } else {
-            info!("\t string: '{}' OK", str);
+            info!("\t string: '{}' OK", report.string);
+        }
+
+        strings.push(report);
+    }
+
+    strings
+}
+
+// No stage is given to the failed strings if the course regex could not be read back
+fn localize_faults(
+    strings: &mut [StringReport],
+    conversion: &Conversion,
+    printed: &Result<Automata, String>,
+) {
+    let printed = match printed {
+        Ok(printed) => printed,
+        Err(e) => {
+            error!("\t no stage at fault: {}", e);
+            return;
+        }
+    };
+
+    let triage = Triage::new(&conversion.automata, printed);
+    for report in strings.iter_mut().filter(|s| s.is_failed()) {
+        let fault = triage.localize(&report.string, report.expected.unwrap());
+        error!("\t '{}' at fault: {}", report.string, fault.name());
+        report.fault = Some(fault);
+    }
+}
+
+// The shortest string the regexes disagree on, which is at most as long as `str`
+fn shrink_string(
+    conversion: &Conversion,
+    with_lookahead: &Regex,
+    round_trip_word: Option<&str>,
+    str: &str,
+) -> String {
+    let without_lookahead = conversion.without_lookahead.as_ref();
+    let is_failing = |str: &str| {
+        matches!(
+            (with_lookahead.is_match(str), is_match(without_lookahead, str)),
+            (Ok(lhs), Ok(rhs)) if lhs != rhs
+        )
+    };
+
+    let mut alphabet = dfa::get_alphabet(&[&conversion.automata]);
+    alphabet.extend(str.chars());
+    alphabet.sort_unstable();
+    alphabet.dedup();
+
+    let mut minimal_string = shrinker::shrink_string(str, &alphabet, is_failing);
+
+    // The regex may lose words of the automata, then the shortest of them is a candidate
+    if let Some(word) = round_trip_word {
+        if word.chars().count() < minimal_string.chars().count() && is_failing(word) {
+            minimal_string = word.to_string();
+        }
+    }
+
+    // Nothing shorter fails if the search is complete
+    let max_length = minimal_string.chars().count().saturating_sub(1);
+    if !minimal_string.is_empty() {
+        if let Some(shortest) = shrinker::find_shortest(&alphabet, max_length, is_failing) {
+            minimal_string = shortest;
+        }
+    }
+
+    minimal_string
+}
+
+// The regexes disagree on one of `strings` or of the strings generated for `r`
+fn is_failing(
+    convertor: &mut Convertor,
+    r: &str,
+    strings: &[String],
+    strs_count: usize,
+    options: &ToRegexOptions,
+    seed: u64,
+    budget: &Budget,
+) -> bool {
+    !find_failed_strings(convertor, r, strings, strs_count, options, seed, budget).is_empty()
+}
+
+// Of `strings` and of the strings generated for `r`, the ones the regexes disagree on
+fn find_failed_strings(
+    convertor: &mut Convertor,
+    r: &str,
+    strings: &[String],
+    strs_count: usize,
+    options: &ToRegexOptions,
+    seed: u64,
+    budget: &Budget,
+) -> Vec<String> {
+    // A panicking candidate does not fail the same way, so it is skipped
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        let (Ok(with_lookahead), Ok(conversion)) = (Regex::new(r), convert(convertor, r, options, budget.clone()))
+        else {
+            return Vec::new();
+        };
+
+        let mut str_gen = str_generator::StringGenerator::from_automata(
+            &conversion.automata,
+            StdRng::seed_from_u64(seed),
+        );
+
+        strings
+            .iter()
+            .cloned()
+            .chain(str_gen.gen_strs(strs_count))
+            .filter(|str| {
+                matches!(
+                    (with_lookahead.is_match(str), is_match(conversion.without_lookahead.as_ref(), str)),
+                    (Ok(lhs), Ok(rhs)) if lhs != rhs
+                )
+            })
+            .collect()
+    }))
+    .unwrap_or_default()
+}
+
+// None stands for the empty language, which matches nothing
+fn get_pcre_regex(regex: Option<String>) -> Result<Option<Regex>, RegexError> {
+    regex
+        .map(|regex| {
+            Regex::new(&regex).map_err(|e| RegexError {
+                class: FailureClass::InvalidPrintedRegex,
+                message: format!("{regex}: {e}"),
+            })
+        })
+        .transpose()
+}
+
+fn is_match(regex: Option<&Regex>, str: &str) -> Result<bool, fancy_regex::Error> {
+    regex.map_or(Ok(false), |regex| regex.is_match(str))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        convert, get_regex_seeds, run_isolated, run_tests, run_tests_for_regex, test_strings,
+        Checks,
+    };
+    use crate::convertor::Convertor;
+    use crate::fuzz::regex_generator::Config;
+    use crate::fuzz::report::FailureClass;
+    use crate::ndfa::limits::Budget;
+    use crate::ndfa::ToRegexOptions;
+
+    #[test]
+    fn panic_isolation() {
+        let report = run_isolated("^a$", Some(3), || panic!("no automata for {}", "^a$"));
+
+        assert_eq!(report.regex, "^a$");
+        assert_eq!(report.seed, Some(3));
+        let error = report.error.as_ref().unwrap();
+        assert_eq!(error.class, FailureClass::ConversionPanic);
+        assert_eq!(error.message, "no automata for ^a$");
+        assert!(!report.is_passed());
+    }
+
+    #[test]
+    fn regex_seeds() {
+        let seeds = get_regex_seeds(1, 100);
+
+        assert_eq!(seeds[..10], get_regex_seeds(1, 10));
+        assert!(get_regex_seeds(2, 100).iter().all(|s| !seeds.contains(s)));
+    }
+
+    #[test]
+    fn generator_panic() {
+        // The generator can not pick a symbol of an empty alphabet
+        let cfg = Config {
+            max_lookahead_count: 1,
+            star_height: 1,
+            alphabet_size: 0,
+            max_letter_count: 3,
+        };
+        let report = run_tests(
+            &Convertor::default(),
+            &[5, 6],
+            1,
+            &cfg,
+            &ToRegexOptions::default(),
+            Checks::default(),
+            2,
+        );
+
+        assert_eq!(report.regexes.len(), 2);
+        for (regex, seed) in report.regexes.iter().zip(5..) {
+            assert_eq!(regex.regex, "");
+            assert_eq!(regex.seed, Some(seed));
+            assert_eq!(
+                regex.error.as_ref().map(|e| e.class),
+                Some(FailureClass::ConversionPanic)
+            );
         }
     }
+
+    #[test]
+    fn classification() {
+        let mut convertor = Convertor::default();
+        let options = ToRegexOptions::default();
+
+        let checks = Checks {
+            shrink: false,
+            round_trip: true,
+        };
+
+        let report = run_tests_for_regex(&mut convertor, "^(a$", 5, &options, 1, checks);
+        assert_eq!(report.failures(), vec![FailureClass::ParseError]);
+
+        let report = run_tests_for_regex(&mut convertor, "^a(?=b)b*$", 5, &options, 1, checks);
+        assert!(report.is_passed());
+    }
+
+    #[test]
+    fn witness_positions() {
+        let options = ToRegexOptions::default();
+        let mut convertor = Convertor::default();
+
+        let conversion = convert(&mut convertor, "^ab*$", &options, Budget::default()).unwrap();
+        let strings = test_strings(&conversion, vec![("ab".to_string(), Ok(false))]);
+        assert_eq!(
+            strings[0].witness.as_deref(),
+            Some("accepted by q0, a1, b2")
+        );
+
+        let conversion = convert(&mut convertor, "^a(?=b)b$", &options, Budget::default()).unwrap();
+        let strings = test_strings(&conversion, vec![("ab".to_string(), Ok(false))]);
+        assert_eq!(
+            strings[0].witness.as_deref(),
+            Some("accepted by q0, q1, q2")
+        );
+    }
 }
@@ -0,0 +1,394 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+/*
+ * Regexes of the generator, with anchors around the root:
+ *
+ * <union> ::= <concat> ('|' <concat>)*
+ * <concat> ::= (<atomic> ('*')*)*
+ * <atomic> ::= SYMBOL | '(' <union> ')' | '(?=' <union> ('$')? ')'
+ */
+
+// Every candidate is checked, so the shrinking is bounded
+const MAX_ATTEMPTS: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Symbol(char),
+    Concat(Vec<Node>),
+    Union(Vec<Node>),
+    Star(Box<Node>),
+    /// The flag is set for a lookahead up to the end of the string
+    Lookahead(Box<Node>, bool),
+}
+
+/// Simplifies `regex` while `is_failing` holds for it: drops alternatives, stars,
+/// lookaheads and parts of concatenations. The result is `regex` itself if nothing
+/// simpler fails or it can not be parsed, as with alternatives outside of brackets.
+pub fn shrink(regex: &str, mut is_failing: impl FnMut(&str) -> bool) -> String {
+    let Some(mut root) = parse(regex) else {
+        return regex.to_string();
+    };
+
+    let mut attempts = 0;
+    'shrinking: loop {
+        for candidate in get_candidates(&root, true) {
+            if attempts == MAX_ATTEMPTS {
+                break 'shrinking;
+            }
+            attempts += 1;
+
+            if is_failing(&to_regex(&candidate)) {
+                root = candidate;
+                continue 'shrinking;
+            }
+        }
+
+        break;
+    }
+
+    to_regex(&root)
+}
+
+/// Removes chunks of `string` and replaces its symbols with the preceding ones of
+/// `alphabet` while `is_failing` holds for it.
+pub fn shrink_string(
+    string: &str,
+    alphabet: &[char],
+    mut is_failing: impl FnMut(&str) -> bool,
+) -> String {
+    let mut symbols: Vec<char> = string.chars().collect();
+
+    let mut attempts = 0;
+    'shrinking: loop {
+        for candidate in get_string_candidates(&symbols, alphabet) {
+            if attempts == MAX_ATTEMPTS {
+                break 'shrinking;
+            }
+            attempts += 1;
+
+            if is_failing(&candidate.iter().collect::<String>()) {
+                symbols = candidate;
+                continue 'shrinking;
+            }
+        }
+
+        break;
+    }
+
+    symbols.into_iter().collect()
+}
+
+/// The first failing string over `alphabet` by length and then by symbols, None if
+/// none is at most `max_length` long or the search is too long.
+pub fn find_shortest(
+    alphabet: &[char],
+    max_length: usize,
+    mut is_failing: impl FnMut(&str) -> bool,
+) -> Option<String> {
+    let mut strings = vec![String::new()];
+    let mut attempts = 0;
+
+    for length in 0..=max_length {
+        for string in &strings {
+            if attempts == MAX_ATTEMPTS {
+                return None;
+            }
+            attempts += 1;
+
+            if is_failing(string) {
+                return Some(string.clone());
+            }
+        }
+
+        if length < max_length {
+            strings = strings
+                .iter()
+                .flat_map(|string| {
+                    alphabet
+                        .iter()
+                        .map(move |symbol| format!("{string}{symbol}"))
+                })
+                .take(MAX_ATTEMPTS - attempts)
+                .collect();
+        }
+    }
+
+    None
+}
+
+// Shorter strings go first, then the ones with smaller symbols
+fn get_string_candidates(symbols: &[char], alphabet: &[char]) -> Vec<Vec<char>> {
+    let mut candidates = Vec::new();
+
+    let mut chunk_size = (symbols.len() / 2).max(1);
+    while chunk_size > 0 && !symbols.is_empty() {
+        for start in (0..symbols.len()).step_by(chunk_size) {
+            let end = (start + chunk_size).min(symbols.len());
+            let mut rest = symbols.to_vec();
+            rest.drain(start..end);
+            candidates.push(rest);
+        }
+        chunk_size /= 2;
+    }
+
+    for (i, symbol) in symbols.iter().enumerate() {
+        for replacement in alphabet
+            .iter()
+            .take_while(|&replacement| replacement < symbol)
+        {
+            let mut replaced = symbols.to_vec();
+            replaced[i] = *replacement;
+            candidates.push(replaced);
+        }
+    }
+
+    candidates
+}
+
+fn parse(regex: &str) -> Option<Node> {
+    let regex = regex.strip_prefix('^')?.strip_suffix('$')?;
+
+    let mut stream = regex.chars().peekable();
+    let root = parse_union(&mut stream)?;
+
+    // The anchors bind to the outer alternatives, brackets around them would change the regex
+    if matches!(root, Node::Union(_)) || stream.peek().is_some() {
+        return None;
+    }
+
+    Some(root)
+}
+
+fn parse_union(stream: &mut Peekable<Chars<'_>>) -> Option<Node> {
+    let mut concats = vec![parse_concat(stream)?];
+
+    while stream.peek() == Some(&'|') {
+        stream.next();
+        concats.push(parse_concat(stream)?);
+    }
+
+    Some(if concats.len() == 1 {
+        concats.pop().unwrap()
+    } else {
+        Node::Union(concats)
+    })
+}
+
+fn parse_concat(stream: &mut Peekable<Chars<'_>>) -> Option<Node> {
+    let mut nodes = Vec::new();
+
+    while let Some(&symbol) = stream.peek() {
+        if symbol == '|' || symbol == ')' || symbol == '$' {
+            break;
+        }
+
+        let mut node = parse_atomic(stream)?;
+        while stream.peek() == Some(&'*') {
+            stream.next();
+            node = Node::Star(Box::new(node));
+        }
+
+        // Brackets only group, so the nested concatenations are flattened
+        match node {
+            Node::Concat(inner) => nodes.extend(inner),
+            node => nodes.push(node),
+        }
+    }
+
+    Some(if nodes.len() == 1 {
+        nodes.pop().unwrap()
+    } else {
+        Node::Concat(nodes)
+    })
+}
+
+fn parse_atomic(stream: &mut Peekable<Chars<'_>>) -> Option<Node> {
+    let symbol = stream.next()?;
+
+    if symbol != '(' {
+        return symbol.is_alphanumeric().then_some(Node::Symbol(symbol));
+    }
+
+    let is_lookahead = stream.peek() == Some(&'?');
+    if is_lookahead && (stream.next(), stream.next()) != (Some('?'), Some('=')) {
+        return None;
+    }
+
+    let node = parse_union(stream)?;
+
+    let is_string_end = is_lookahead && stream.peek() == Some(&'$');
+    if is_string_end {
+        stream.next();
+    }
+
+    if stream.next() != Some(')') {
+        return None;
+    }
+
+    Some(if is_lookahead {
+        Node::Lookahead(Box::new(node), is_string_end)
+    } else {
+        node
+    })
+}
+
+fn to_regex(root: &Node) -> String {
+    format!("^{}$", print(root))
+}
+
+fn print(node: &Node) -> String {
+    match node {
+        Node::Symbol(symbol) => symbol.to_string(),
+        Node::Concat(nodes) => nodes.iter().map(print).collect(),
+        Node::Union(concats) => format!(
+            "({})",
+            concats.iter().map(print).collect::<Vec<_>>().join("|")
+        ),
+        Node::Star(node) => match node.as_ref() {
+            Node::Symbol(symbol) => format!("{symbol}*"),
+            Node::Union(_) => format!("{}*", print(node)),
+            node => format!("({})*", print(node)),
+        },
+        Node::Lookahead(node, is_string_end) => {
+            format!(
+                "(?={}{})",
+                print(node),
+                if *is_string_end { "$" } else { "" }
+            )
+        }
+    }
+}
+
+// Simpler nodes to replace `node` with, the biggest simplifications go first
+fn get_candidates(node: &Node, is_root: bool) -> Vec<Node> {
+    let mut candidates = Vec::new();
+
+    match node {
+        Node::Symbol(_) => {}
+        Node::Concat(nodes) => {
+            // Chunks of halving size are removed, as in delta debugging
+            let mut chunk_size = nodes.len() / 2;
+            while chunk_size > 0 {
+                for start in (0..nodes.len()).step_by(chunk_size) {
+                    let end = (start + chunk_size).min(nodes.len());
+                    let mut rest = nodes.clone();
+                    rest.drain(start..end);
+                    candidates.push(new_concat(rest));
+                }
+                chunk_size /= 2;
+            }
+
+            // Only the root may stand for ε
+            if is_root {
+                candidates.push(Node::Concat(Vec::new()));
+            }
+
+            for (i, child) in nodes.iter().enumerate() {
+                for child_candidate in get_candidates(child, false) {
+                    let mut nodes = nodes.clone();
+                    nodes[i] = child_candidate;
+                    candidates.push(new_concat(nodes));
+                }
+            }
+        }
+        Node::Union(concats) => {
+            for i in 0..concats.len() {
+                let mut rest = concats.clone();
+                rest.remove(i);
+                candidates.push(new_union(rest));
+            }
+
+            for (i, concat) in concats.iter().enumerate() {
+                for concat_candidate in get_candidates(concat, false) {
+                    let mut concats = concats.clone();
+                    concats[i] = concat_candidate;
+                    candidates.push(Node::Union(concats));
+                }
+            }
+        }
+        Node::Star(inner) => {
+            candidates.push(inner.as_ref().clone());
+            candidates.extend(
+                get_candidates(inner, false)
+                    .into_iter()
+                    .map(|inner| Node::Star(Box::new(inner))),
+            );
+        }
+        Node::Lookahead(inner, is_string_end) => {
+            if *is_string_end {
+                candidates.push(Node::Lookahead(inner.clone(), false));
+            }
+            candidates.extend(
+                get_candidates(inner, false)
+                    .into_iter()
+                    .map(|inner| Node::Lookahead(Box::new(inner), *is_string_end)),
+            );
+        }
+    }
+
+    // Empty brackets and stars of nothing are not valid regexes
+    candidates.retain(|candidate| is_root || *candidate != Node::Concat(Vec::new()));
+    candidates
+}
+
+fn new_concat(mut nodes: Vec<Node>) -> Node {
+    if nodes.len() == 1 {
+        nodes.pop().unwrap()
+    } else {
+        Node::Concat(nodes)
+    }
+}
+
+fn new_union(mut concats: Vec<Node>) -> Node {
+    if concats.len() == 1 {
+        concats.pop().unwrap()
+    } else {
+        Node::Union(concats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_shortest, parse, shrink, shrink_string, to_regex};
+
+    #[test]
+    fn round_trip() {
+        for regex in ["^(a|b)*c(?=b*a$)d$", "^((ab)*|c)*(?=(a|cc))$", "^$"] {
+            assert_eq!(to_regex(&parse(regex).unwrap()), regex);
+        }
+
+        assert_eq!(to_regex(&parse("^(a)*((b))(c)$").unwrap()), "^a*bc$");
+        assert!(parse("^(a$").is_none());
+        assert!(parse("^a|b$").is_none());
+    }
+
+    #[test]
+    fn shrinking() {
+        assert_eq!(
+            shrink("^(a|b)*c(?=(b)*a$)d$", |r| r.contains("(?=")),
+            "^(?=a)$"
+        );
+        assert_eq!(
+            shrink("^((ab)*|c)*(c|a)$", |r| r.contains('b') && r.contains('c')),
+            "^(b|c)$"
+        );
+        assert_eq!(shrink("^abc$", |_| false), "^abc$");
+    }
+
+    #[test]
+    fn strings() {
+        let is_failing = |s: &str| s.contains('b') && s.ends_with('c');
+
+        assert_eq!(
+            shrink_string("acabbacaac", &['a', 'b', 'c'], is_failing),
+            "bc"
+        );
+        assert_eq!(shrink_string("abc", &['a', 'b', 'c'], |_| false), "abc");
+        assert_eq!(
+            find_shortest(&['a', 'b', 'c'], 3, |s| s.len() == 2 && s > "ba"),
+            Some("bb".to_string())
+        );
+        assert_eq!(find_shortest(&['a', 'b'], 3, |s| s.len() > 3), None);
+    }
+}
@@ -2,7 +2,7 @@ use std::collections::HashSet;
 use std::collections::VecDeque;
 
 use ndarray::Array2;
-use rand::rngs::ThreadRng;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
 use rand::Rng;
 
@@ -11,7 +11,7 @@ use crate::ndfa;
 pub struct StringGenerator<'a> {
     automata: &'a ndfa::Automata,
     reachability: Reachability,
-    rng: ThreadRng,
+    rng: StdRng,
 }
 
 impl<'a> StringGenerator<'a> {
@@ -24,11 +24,12 @@ impl<'a> StringGenerator<'a> {
 
     const MUTATIONS_COUNT: usize = 6;
 
-    pub fn from_automata(automata: &'a ndfa::Automata) -> Self {
+    /// The same seeded `rng` gives the same strings.
+    pub fn from_automata(automata: &'a ndfa::Automata, rng: StdRng) -> Self {
         Self {
             automata,
             reachability: Reachability::from_automata(automata),
-            rng: rand::thread_rng(),
+            rng,
         }
     }
 
@@ -69,8 +70,8 @@ impl<'a> StringGenerator<'a> {
             let next_state = self.reachability.as_outcoming[current_state]
                 .choose(&mut self.rng)
                 .unwrap();
-            states.push(next_state.clone());
-            current_state = next_state.clone();
+            states.push(*next_state);
+            current_state = *next_state;
         }
 
         // Epsilon corner case
@@ -89,12 +90,12 @@ impl<'a> StringGenerator<'a> {
         let mut words_chain = Vec::<String>::with_capacity(states_chain.len() - 1);
 
         let mut first_iter = states_chain.iter();
-        let mut second_iter = states_chain.iter().skip(1);
+        let second_iter = states_chain.iter().skip(1);
 
-        while let Some(to) = second_iter.next() {
+        for to in second_iter {
             let from = first_iter.next().unwrap();
 
-            words_chain.push(self.gen_word(&from, &to));
+            words_chain.push(self.gen_word(from, to));
             // NOTE: можно генерировать несколько слов на отрезке.
         }
 
@@ -103,7 +104,7 @@ impl<'a> StringGenerator<'a> {
 
     fn gen_word(&mut self, from: &usize, to: &usize) -> String {
         let mut states_deq = VecDeque::<(String, usize)>::new();
-        states_deq.push_back((String::new(), from.clone()));
+        states_deq.push_back((String::new(), *from));
 
         while let Some((word_prefix, state)) = states_deq.pop_front() {
             let mut outcoming = Vec::<(String, usize)>::new();
@@ -145,7 +146,7 @@ impl<'a> StringGenerator<'a> {
         }
     }
 
-    fn swap_words(&mut self, words: &mut Vec<String>) {
+    fn swap_words(&mut self, words: &mut [String]) {
         if words.len() <= 1 {
             return;
         }
@@ -156,7 +157,7 @@ impl<'a> StringGenerator<'a> {
         words.swap(i, j);
     }
 
-    fn swap_letters(&mut self, words: &mut Vec<String>) {
+    fn swap_letters(&mut self, words: &mut [String]) {
         if words.is_empty() {
             return;
         }
@@ -184,7 +185,7 @@ impl<'a> StringGenerator<'a> {
         words.insert(i, words[i].to_string());
     }
 
-    fn duplicate_letter(&mut self, words: &mut Vec<String>) {
+    fn duplicate_letter(&mut self, words: &mut [String]) {
         if words.is_empty() {
             return;
         }
@@ -209,7 +210,7 @@ impl<'a> StringGenerator<'a> {
         words.remove(i);
     }
 
-    fn remove_letter(&mut self, words: &mut Vec<String>) {
+    fn remove_letter(&mut self, words: &mut [String]) {
         if words.is_empty() {
             return;
         }
@@ -223,19 +224,19 @@ impl<'a> StringGenerator<'a> {
         words[i].remove(j);
     }
 
-    fn choose_word(&mut self, words: &Vec<String>) -> usize {
+    fn choose_word(&mut self, words: &[String]) -> usize {
         debug_assert!(!words.is_empty());
 
         self.rng.gen_range(0..words.len())
     }
 
-    fn choose_letter(&mut self, word: &String) -> usize {
+    fn choose_letter(&mut self, word: &str) -> usize {
         debug_assert!(!word.is_empty());
 
         self.rng.gen_range(0..word.len())
     }
 
-    fn join_words(&self, words: &Vec<String>) -> String {
+    fn join_words(&self, words: &[String]) -> String {
         let mut result = String::new();
 
         for word in words.iter() {
@@ -300,7 +301,7 @@ impl Reachability {
         outcoming
     }
 
-    fn get_incoming(outcoming: &Vec<Vec<usize>>) -> Vec<HashSet<usize>> {
+    fn get_incoming(outcoming: &[Vec<usize>]) -> Vec<HashSet<usize>> {
         let mut incoming = vec![HashSet::<usize>::new(); outcoming.len()];
 
         for (i, row) in outcoming.iter().enumerate() {
@@ -312,3 +313,27 @@ impl Reachability {
         incoming
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::StringGenerator;
+    use crate::convertor;
+
+    #[test]
+    fn seeded() {
+        // Every conversion hashes the product states anew
+        let regex = "^(?=ab|ba)(a|b)*(?=(a|b)*b$)(a|b)*$";
+        let gen_strs = || {
+            let automata = convertor::gen_rec(regex).unwrap();
+            StringGenerator::from_automata(&automata, StdRng::seed_from_u64(7)).gen_strs(6)
+        };
+
+        let strs = gen_strs();
+        for _ in 0..4 {
+            assert_eq!(gen_strs(), strs);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::ndfa::Automata;
+
+/*
+ * A string goes through the artifacts of the conversion in order:
+ *
+ * regex with lookaheads --convertor--> automata --to_regex--> course regex
+ *     --dialect--> printed regex
+ *
+ * The verdict of fancy-regex on the first one is the expected one, the course
+ * regex is read back into an automata to get its verdict.
+ */
+
+/// Step of the conversion that changed the verdict on a string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Stage {
+    /// Building the automata for the regex with lookaheads
+    Convertor,
+    /// Converting the automata back to a regex
+    ToRegex,
+    /// Printing the regex in the dialect of the options
+    Dialect,
+}
+
+impl Stage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Convertor => "convertor",
+            Stage::ToRegex => "to_regex",
+            Stage::Dialect => "dialect",
+        }
+    }
+}
+
+/// The intermediate automatas of a conversion.
+pub struct Triage<'a> {
+    automata: &'a Automata,
+    printed: &'a Automata,
+}
+
+impl<'a> Triage<'a> {
+    /// `printed` is the course regex of `automata` read back, the caller builds it
+    /// under the limits of the conversion.
+    pub fn new(automata: &'a Automata, printed: &'a Automata) -> Self {
+        Self { automata, printed }
+    }
+
+    /// The first stage whose verdict on `string` differs from the one before it, for
+    /// a string the regexes disagree on.
+    pub fn localize(&self, string: &str, expected: bool) -> Stage {
+        let in_automata = self.automata.find_witness(string).is_accepted();
+        let in_printed = self.printed.find_witness(string).is_accepted();
+
+        if in_automata != expected {
+            Stage::Convertor
+        } else if in_printed != in_automata {
+            Stage::ToRegex
+        } else {
+            Stage::Dialect
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Stage, Triage};
+    use crate::ndfa::Automata;
+
+    #[test]
+    fn localization() {
+        let automata = Automata::from_regex("a(b|c)*");
+        let printed = Automata::from_printed_regex(&automata.to_regex().unwrap());
+        let triage = Triage::new(&automata, &printed);
+
+        assert_eq!(triage.localize("ab", false), Stage::Convertor);
+        assert_eq!(triage.localize("abc", true), Stage::Dialect);
+
+        let printed = Automata::from_regex("ab");
+        let triage = Triage::new(&automata, &printed);
+        assert_eq!(triage.localize("ac", true), Stage::ToRegex);
+        assert_eq!(triage.localize("ab", true), Stage::Dialect);
+    }
+}
@@ -0,0 +1,46 @@
+/*
+ * FNV-1a with 64 bits. The file names of the corpus and of the cache are
+ * derived from it, so unlike the ones of `DefaultHasher` they stay the same
+ * across Rust releases.
+ *
+ * splitmix64 spreads the seeds of the fuzzed regexes, so runs with close seeds
+ * share no regexes.
+ */
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+pub fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Output `i` of the splitmix64 generator seeded with `seed`, counting from 0.
+pub fn splitmix64(seed: u64, i: u64) -> u64 {
+    let mut z = seed.wrapping_add(i.wrapping_add(1).wrapping_mul(GOLDEN_GAMMA));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{splitmix64, stable_hash};
+
+    #[test]
+    fn fnv1a() {
+        assert_eq!(stable_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(stable_hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(stable_hash("foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn splitmix() {
+        assert_eq!(splitmix64(0, 0), 0xe220_a839_7b1d_cdaf);
+        assert_eq!(splitmix64(0, 1), 0x6e78_9e6a_a1b9_65f4);
+        assert_ne!(splitmix64(1, 1), splitmix64(2, 0));
+    }
+}
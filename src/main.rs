@@ -1,74 +1,554 @@
-use fuzz::{regex_generator, runner};
+use std::path::PathBuf;
+use std::time::Duration;
 
+use commands::Input;
+use convertor::Convertor;
+use fuzz::regex_generator;
+use fuzz::report::{FuzzReport, ReportFormat};
+use fuzz::runner::{self, Checks};
+use ndfa::elimination::EliminationOrder;
+use ndfa::limits::Limits;
+use ndfa::printer::Dialect;
+use ndfa::render::RenderFormat;
+use ndfa::trace::TraceFormat;
+use ndfa::{ConversionMethod, ToRegexOptions};
+use output::OutputFormat;
+
+#[cfg(feature = "serde")]
+pub mod cache;
+pub mod commands;
 pub mod convertor;
 pub mod fuzz;
+pub mod hash;
 pub mod ndfa;
+pub mod output;
 pub mod parser;
-use clap::Parser;
+use clap::{ArgGroup, Parser, Subcommand};
 
 #[derive(Parser)]
 struct Args {
-    #[clap(value_parser, long)]
-    regex_count: Option<usize>,
-    #[clap(value_parser, long)]
-    string_count: Option<usize>,
-    #[clap(value_parser, long)]
+    #[command(subcommand)]
+    command: Command,
+    /// Load the automatas from the directory and store the new ones there
+    #[cfg(feature = "serde")]
+    #[clap(value_parser, long, global = true)]
+    cache_dir: Option<PathBuf>,
+}
+
+/// A regex with lookaheads or an automata file.
+#[derive(clap::Args)]
+struct InputArgs {
+    #[clap(value_parser, long, required_unless_present = "automata")]
     regex: Option<String>,
+    /// .jff, .json or .msgpack with the serde feature, AT&T text otherwise
+    #[clap(value_parser, long, conflicts_with = "regex")]
+    automata: Option<PathBuf>,
+    /// Symbol table of the AT&T files, labels are symbol ids instead of names then
     #[clap(value_parser, long)]
-    lookahead_count: Option<usize>,
+    att_symbols: Option<PathBuf>,
+}
+
+impl InputArgs {
+    fn to_input(&self) -> Input<'_> {
+        match (&self.regex, &self.automata) {
+            (Some(regex), _) => Input::Regex(regex),
+            (None, Some(path)) => Input::Automata {
+                path,
+                att_symbols: self.att_symbols.as_deref(),
+            },
+            (None, None) => unreachable!("clap requires the regex or the automata"),
+        }
+    }
+}
+
+#[derive(clap::Args)]
+struct ConversionArgs {
+    #[clap(value_enum, long, default_value = "course")]
+    dialect: Dialect,
+    #[clap(value_enum, long, default_value = "state-elimination")]
+    method: ConversionMethod,
+    #[clap(value_enum, long, default_value = "index")]
+    elimination_order: EliminationOrder,
+    #[clap(value_parser, long, default_value_t = 4)]
+    search_width: usize,
+    #[clap(long)]
+    no_simplify: bool,
+}
+
+impl ConversionArgs {
+    fn to_options(&self) -> ToRegexOptions {
+        ToRegexOptions {
+            dialect: self.dialect,
+            method: self.method,
+            order: self.elimination_order,
+            search_width: self.search_width,
+            simplify: !self.no_simplify,
+        }
+    }
+}
+
+#[derive(clap::Args)]
+struct LimitArgs {
+    /// Product states of an intersection before the regex times out
     #[clap(value_parser, long)]
-    star_height: Option<usize>,
+    max_product_states: Option<usize>,
+    /// States of an automata before the regex times out
     #[clap(value_parser, long)]
-    letter_count: Option<usize>,
+    max_matrix_size: Option<usize>,
+    /// Seconds to build the automata of a regex and convert it back
+    #[clap(value_parser, long)]
+    timeout: Option<f64>,
+}
+
+impl LimitArgs {
+    fn to_limits(&self) -> Limits {
+        Limits {
+            max_product_states: self.max_product_states,
+            max_matrix_size: self.max_matrix_size,
+            timeout: self.timeout.map(Duration::from_secs_f64),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Convert a regex with lookaheads or an automata to a regex
+    Convert {
+        #[command(flatten)]
+        input: InputArgs,
+        #[command(flatten)]
+        conversion: ConversionArgs,
+        /// Convert by state elimination and by Arden's lemma and compare the results
+        #[clap(long)]
+        compare_methods: bool,
+        #[clap(value_enum, long, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Test the conversion of generated regexes on generated strings, fails on any mismatch
+    Fuzz {
+        #[clap(value_parser, long, default_value_t = 50)]
+        regex_count: usize,
+        #[clap(value_parser, long, default_value_t = 10)]
+        string_count: usize,
+        #[clap(value_parser, long, default_value_t = 4)]
+        lookahead_count: usize,
+        #[clap(value_parser, long, default_value_t = 2)]
+        star_height: usize,
+        #[clap(value_parser, long, default_value_t = 3)]
+        alphabet_size: i32,
+        #[clap(value_parser, long, default_value_t = 10)]
+        letter_count: usize,
+        /// Of the run, random if not given; the regexes get their seeds from it
+        #[clap(value_parser, long)]
+        seed: Option<u64>,
+        /// Test the one regex with this seed from a report instead
+        #[clap(value_parser, long, conflicts_with = "seed")]
+        regex_seed: Option<u64>,
+        /// Report failing regexes as they are, without looking for the minimal one
+        #[clap(long)]
+        no_shrink: bool,
+        /// Also check that the regex of every automata read back describes it
+        #[clap(long)]
+        round_trip: bool,
+        /// Regexes tested at once, the number of CPUs if not given
+        #[clap(value_parser, long)]
+        jobs: Option<usize>,
+        #[command(flatten)]
+        conversion: ConversionArgs,
+        #[command(flatten)]
+        limits: LimitArgs,
+        /// Add the failed strings and minimal failing regexes to the corpus directory
+        #[clap(value_parser, long)]
+        corpus: Option<PathBuf>,
+        #[clap(value_enum, long, default_value = "text")]
+        format: ReportFormat,
+        /// Write the report to the file instead of stdout
+        #[clap(value_parser, long)]
+        output: Option<PathBuf>,
+    },
+    /// Test the conversion of a regex on generated strings, fails on any mismatch
+    Check {
+        #[clap(value_parser, long)]
+        regex: String,
+        #[clap(value_parser, long, default_value_t = 10)]
+        string_count: usize,
+        /// Random if not given, printed in the report
+        #[clap(value_parser, long)]
+        seed: Option<u64>,
+        /// Report failing regexes as they are, without looking for the minimal one
+        #[clap(long)]
+        no_shrink: bool,
+        /// Also check that the regex of every automata read back describes it
+        #[clap(long)]
+        round_trip: bool,
+        #[command(flatten)]
+        conversion: ConversionArgs,
+        #[command(flatten)]
+        limits: LimitArgs,
+        /// Add the failed strings and minimal failing regexes to the corpus directory
+        #[clap(value_parser, long)]
+        corpus: Option<PathBuf>,
+        #[clap(value_enum, long, default_value = "text")]
+        format: ReportFormat,
+        /// Write the report to the file instead of stdout
+        #[clap(value_parser, long)]
+        output: Option<PathBuf>,
+    },
+    /// Rerun the regression corpus, fails if a stored verdict is not reproduced
+    Replay {
+        /// The directory filled by --corpus of fuzz and check
+        #[clap(value_parser, long)]
+        corpus: PathBuf,
+        #[command(flatten)]
+        conversion: ConversionArgs,
+        #[command(flatten)]
+        limits: LimitArgs,
+        #[clap(value_enum, long, default_value = "text")]
+        format: ReportFormat,
+        /// Write the report to the file instead of stdout
+        #[clap(value_parser, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print the automata for a regex or from a file as a diagram
+    Render {
+        #[command(flatten)]
+        input: InputArgs,
+        #[clap(value_enum, long, default_value = "dot")]
+        format: RenderFormat,
+        /// Write the diagram to the file instead of stdout
+        #[clap(value_parser, long)]
+        output: Option<PathBuf>,
+    },
+    /// Check whether two regexes or automatas define the same language
+    Equiv {
+        /// Given twice, or once together with --automata
+        #[clap(value_parser, long)]
+        regex: Vec<String>,
+        #[clap(value_parser, long)]
+        automata: Vec<PathBuf>,
+        #[clap(value_parser, long)]
+        att_symbols: Option<PathBuf>,
+        #[clap(value_enum, long, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Print the minimal complete DFA
+    Minimize {
+        #[command(flatten)]
+        input: InputArgs,
+        #[clap(value_enum, long, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Print strings generated by the automata
+    GenStrings {
+        #[command(flatten)]
+        input: InputArgs,
+        #[clap(value_parser, long, default_value_t = 10)]
+        count: usize,
+        /// Random if not given
+        #[clap(value_parser, long)]
+        seed: Option<u64>,
+        #[clap(value_enum, long, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Write the automata to JFLAP, AT&T or DOT files
+    #[command(group(ArgGroup::new("target").required(true).multiple(true)))]
+    Export {
+        #[command(flatten)]
+        input: InputArgs,
+        #[command(flatten)]
+        conversion: ConversionArgs,
+        #[clap(value_parser, long, group = "target")]
+        jff: Option<PathBuf>,
+        /// The symbol table goes to --att-symbols if it is given
+        #[clap(value_parser, long, group = "target")]
+        att: Option<PathBuf>,
+        /// Write DOT files of the automata after every intersection, concatenation
+        /// and union and of every state elimination step to the directory
+        #[clap(value_parser, long, group = "target", requires = "regex")]
+        dump_dot: Option<PathBuf>,
+    },
+    /// Print how the regex was split to build the automata
+    Explain {
+        #[clap(value_parser, long)]
+        regex: String,
+        #[clap(value_enum, long, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Print every step of the state elimination
+    Trace {
+        #[command(flatten)]
+        input: InputArgs,
+        #[command(flatten)]
+        conversion: ConversionArgs,
+        #[clap(value_enum, long, default_value = "text")]
+        format: TraceFormat,
+        /// File for the text and JSON traces, directory for the DOT frames
+        #[clap(value_parser, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print the marked regex with the First, Last and Follow sets
+    Glushkov {
+        /// In the course notation, without lookaheads
+        #[clap(value_parser, long)]
+        regex: String,
+        #[clap(value_enum, long, default_value = "text")]
+        format: OutputFormat,
+    },
 }
 
 fn main() {
     env_logger::init();
     let cli = Args::parse();
 
-    let mut regex = "".to_string();
-    let mut regex_count = 50;
-    let mut string_count = 10;
-    let mut cfg = regex_generator::Config {
-        max_lookahead_count: 4,
-        star_height: 2,
-        alphabet_size: 3,
-        max_letter_count: 10,
-    };
-
-    match cli.regex {
-        Some(expr) => regex = expr,
-        None => {}
+    #[allow(unused_mut)]
+    let mut convertor = Convertor::default();
+    #[cfg(feature = "serde")]
+    if let Some(dir) = cli.cache_dir {
+        convertor = Convertor::with_cache(dir);
     }
 
-    match cli.regex_count {
-        Some(c) => regex_count = c,
-        None => {}
+    if let Err(e) = run(cli.command, &mut convertor) {
+        log::error!("{}", e);
+        std::process::exit(1);
     }
+}
 
-    match cli.string_count {
-        Some(c) => string_count = c,
-        None => {}
-    }
+fn run(command: Command, convertor: &mut Convertor) -> Result<(), String> {
+    match command {
+        Command::Convert {
+            input,
+            conversion,
+            compare_methods,
+            format,
+        } => {
+            let options = conversion.to_options();
+            if compare_methods {
+                commands::compare_methods(convertor, input.to_input(), &options, format)
+            } else {
+                commands::convert(convertor, input.to_input(), &options, format)
+            }
+            .map_err(|e| format!("failed to convert: {e}"))
+        }
+        Command::Fuzz {
+            regex_count,
+            string_count,
+            lookahead_count,
+            star_height,
+            alphabet_size,
+            letter_count,
+            seed,
+            regex_seed,
+            no_shrink,
+            round_trip,
+            jobs,
+            conversion,
+            limits,
+            corpus,
+            format,
+            output,
+        } => {
+            convertor.set_limits(limits.to_limits());
+            let jobs = jobs.unwrap_or_else(|| {
+                std::thread::available_parallelism().map_or(1, |jobs| jobs.get())
+            });
+            let cfg = regex_generator::Config {
+                max_lookahead_count: lookahead_count,
+                star_height,
+                alphabet_size,
+                max_letter_count: letter_count,
+            };
+            let seeds = match regex_seed {
+                Some(regex_seed) => vec![regex_seed],
+                None => {
+                    let seed = seed.unwrap_or_else(rand::random);
+                    log::info!("fuzzing with seed {seed}");
+                    runner::get_regex_seeds(seed, regex_count)
+                }
+            };
+            let report = runner::run_tests(
+                convertor,
+                &seeds,
+                string_count,
+                &cfg,
+                &conversion.to_options(),
+                Checks {
+                    shrink: !no_shrink,
+                    round_trip,
+                },
+                jobs,
+            );
+            // The report goes out even if some failures can not be stored
+            let written = write_report(&report, format, output);
+            if let Some(corpus) = corpus {
+                runner::store_failures(
+                    convertor,
+                    &corpus,
+                    &report,
+                    string_count,
+                    &conversion.to_options(),
+                );
+            }
 
-    match cli.star_height {
-        Some(h) => cfg.star_height = h,
-        None => {}
-    }
+            written
+        }
+        Command::Check {
+            regex,
+            string_count,
+            seed,
+            no_shrink,
+            round_trip,
+            conversion,
+            limits,
+            corpus,
+            format,
+            output,
+        } => {
+            convertor.set_limits(limits.to_limits());
+            let report = FuzzReport {
+                regexes: vec![runner::run_tests_for_regex(
+                    convertor,
+                    &regex,
+                    string_count,
+                    &conversion.to_options(),
+                    seed.unwrap_or_else(rand::random),
+                    Checks {
+                        shrink: !no_shrink,
+                        round_trip,
+                    },
+                )],
+            };
+            // The report goes out even if some failures can not be stored
+            let written = write_report(&report, format, output);
+            if let Some(corpus) = corpus {
+                runner::store_failures(
+                    convertor,
+                    &corpus,
+                    &report,
+                    string_count,
+                    &conversion.to_options(),
+                );
+            }
 
-    match cli.lookahead_count {
-        Some(c) => cfg.max_letter_count = c,
-        None => {}
-    }
+            written
+        }
+        Command::Replay {
+            corpus,
+            conversion,
+            limits,
+            format,
+            output,
+        } => {
+            convertor.set_limits(limits.to_limits());
+            let report = runner::replay(convertor, &corpus, &conversion.to_options())
+                .map_err(|e| format!("failed to load the corpus: {e}"))?;
+
+            write_report(&report, format, output)
+        }
+        Command::Render {
+            input,
+            format,
+            output,
+        } => commands::render(convertor, input.to_input(), format, output.as_deref())
+            .map_err(|e| format!("failed to render automata: {e}")),
+        Command::Equiv {
+            regex,
+            automata,
+            att_symbols,
+            format,
+        } => {
+            let inputs: Vec<Input<'_>> = regex
+                .iter()
+                .map(|r| Input::Regex(r))
+                .chain(automata.iter().map(|path| Input::Automata {
+                    path,
+                    att_symbols: att_symbols.as_deref(),
+                }))
+                .collect();
+            let inputs: [Input<'_>; 2] = inputs
+                .try_into()
+                .map_err(|_| "equiv needs exactly two regexes or automatas")?;
+
+            commands::equiv(convertor, inputs, format)
+                .map_err(|e| format!("failed to compare: {e}"))
+        }
+        Command::Minimize { input, format } => {
+            commands::minimize(convertor, input.to_input(), format)
+                .map_err(|e| format!("failed to minimize: {e}"))
+        }
+        Command::GenStrings {
+            input,
+            count,
+            seed,
+            format,
+        } => commands::gen_strings(
+            convertor,
+            input.to_input(),
+            count,
+            seed.unwrap_or_else(rand::random),
+            format,
+        )
+        .map_err(|e| format!("failed to generate strings: {e}")),
+        Command::Export {
+            input,
+            conversion,
+            jff,
+            att,
+            dump_dot,
+        } => {
+            if let (Some(dir), Some(regex)) = (&dump_dot, &input.regex) {
+                commands::dump_dot(regex, dir, &conversion.to_options())
+                    .map_err(|e| format!("failed to dump automatas: {e}"))?;
+            }
 
-    match cli.letter_count {
-        Some(c) => cfg.max_letter_count = c,
-        None => {}
+            let automata = input.to_input().load(convertor)?;
+            if let Some(path) = &jff {
+                commands::export_jff(&automata, path)
+                    .map_err(|e| format!("failed to export automata: {e}"))?;
+            }
+            if let Some(path) = &att {
+                commands::export_att(&automata, path, input.att_symbols.as_deref())
+                    .map_err(|e| format!("failed to export automata: {e}"))?;
+            }
+
+            Ok(())
+        }
+        Command::Explain { regex, format } => commands::explain(convertor, &regex, format)
+            .map_err(|e| format!("failed to explain the conversion: {e}")),
+        Command::Trace {
+            input,
+            conversion,
+            format,
+            output,
+        } => commands::trace(
+            convertor,
+            input.to_input(),
+            format,
+            output.as_deref(),
+            &conversion.to_options(),
+        )
+        .map_err(|e| format!("failed to trace the state elimination: {e}")),
+        Command::Glushkov { regex, format } => commands::glushkov(&regex, format)
+            .map_err(|e| format!("failed to build the Glushkov sets: {e}")),
     }
+}
+
+// Mismatches are errors, so the exit code tells pass from fail
+fn write_report(
+    report: &FuzzReport,
+    format: ReportFormat,
+    output: Option<PathBuf>,
+) -> Result<(), String> {
+    report
+        .write(format, output.as_deref())
+        .map_err(|e| format!("failed to write the report: {e}"))?;
 
-    if !regex.is_empty() {
-        runner::run_tests_for_regex(&regex, string_count);
+    if report.is_passed() {
+        Ok(())
     } else {
-        runner::run_tests(regex_count, string_count, &cfg);
+        Err(format!(
+            "{} of {} regexes failed",
+            report.failed_count(),
+            report.regexes.len()
+        ))
     }
 }
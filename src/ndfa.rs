@@ -1,10 +1,36 @@
+pub mod arden;
 pub mod ast;
+pub mod att;
+pub mod dfa;
+pub mod dot;
+pub mod elimination;
+pub mod glushkov;
+pub mod jflap;
+pub mod limits;
+pub mod nfa;
+pub mod printer;
+pub mod render;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod simplify;
+pub mod trace;
+pub mod witness;
 
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
-
-#[derive(Debug)]
+use std::path::Path;
+
+use elimination::EliminationOrder;
+use limits::Budget;
+use log::debug;
+use printer::Dialect;
+#[cfg(feature = "serde")]
+use serialization::Serialization;
+use trace::TransitionStep;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Automata<T = char> {
     pub size: usize,
     pub transition_matrix: Vec<Vec<Option<T>>>,
@@ -17,6 +43,37 @@ pub const START: usize = 0;
 const ARBITARY: char = '.';
 const EPSILON: String = String::new();
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ConversionMethod {
+    StateElimination,
+    /// Solving the system of language equations by Arden's lemma
+    Arden,
+}
+
+/// Trades the conversion speed for the size of `Automata::to_regex` output.
+#[derive(Debug, Clone)]
+pub struct ToRegexOptions {
+    pub dialect: Dialect,
+    pub method: ConversionMethod,
+    /// Ignored by `ConversionMethod::Arden`
+    pub order: EliminationOrder,
+    /// Automata kept on every step of `EliminationOrder::Search`
+    pub search_width: usize,
+    pub simplify: bool,
+}
+
+impl Default for ToRegexOptions {
+    fn default() -> Self {
+        Self {
+            dialect: Dialect::Course,
+            method: ConversionMethod::StateElimination,
+            order: EliminationOrder::Index,
+            search_width: 4,
+            simplify: true,
+        }
+    }
+}
+
 impl<T: std::clone::Clone> Automata<T> {
     fn new(size: usize) -> Self {
         let mut start_states = vec![false; size];
@@ -35,11 +92,11 @@ impl<T: std::clone::Clone> Automata<T> {
     }
 
     pub fn is_start_state(&self, i: usize) -> bool {
-        return self.start_states[i];
+        self.start_states[i]
     }
 
     pub fn is_finite_state(&self, i: usize) -> bool {
-        return self.finite_states[i];
+        self.finite_states[i]
     }
 }
 
@@ -63,11 +120,19 @@ impl Automata {
     }
 
     pub fn from_regex(regex: &str) -> Self {
+        limits::unlimited(|budget| Self::try_from_regex(regex, budget))
+    }
+
+    /// The size is checked before the transition matrix is allocated.
+    pub fn try_from_regex(regex: &str, budget: &Budget) -> Result<Self, String> {
+        budget.check_time()?;
+
         if regex.is_empty() {
-            return Self::new_epsilon();
+            return Ok(Self::new_epsilon());
         }
 
-        let tree = ast::Tree::from_regex(regex);
+        let tree = ast::Tree::try_from_regex(regex, budget)?;
+        budget.check_matrix_size(tree.linearized_symbols + 1)?;
         let mut automata = Self::new(tree.linearized_symbols + 1);
 
         for s in tree.get_first_set() {
@@ -82,33 +147,104 @@ impl Automata {
             automata.finite_states[s.index] = true;
         }
 
-        automata
+        budget.check_time()?;
+        Ok(automata)
+    }
+
+    /// Loads the automata in the format given by the extension: `.jff`, `.json`
+    /// and `.msgpack` with the `serde` feature, the AT&T text format otherwise.
+    pub fn read(path: &Path) -> Result<Self, String> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("jff") => Self::read_jff(path),
+            #[cfg(feature = "serde")]
+            Some("json") => {
+                Self::from_json(&std::fs::read_to_string(path).map_err(|e| e.to_string())?)
+            }
+            #[cfg(feature = "serde")]
+            Some("msgpack") => Self::from_bytes(&std::fs::read(path).map_err(|e| e.to_string())?),
+            _ => Self::read_att(path, None),
+        }
+    }
+
+    /// Reads back the course notation printed by `to_regex`.
+    pub fn from_printed_regex(regex: &str) -> Self {
+        limits::unlimited(|budget| Self::try_from_printed_regex(regex, budget))
+    }
+
+    pub fn try_from_printed_regex(regex: &str, budget: &Budget) -> Result<Self, String> {
+        let regex = regex.strip_prefix('^').unwrap_or(regex);
+        let regex = regex.strip_suffix('$').unwrap_or(regex);
+
+        Self::try_from_regex(regex, budget)
     }
 
     pub fn to_regex(&self) -> Option<String> {
-        let mut automata = self.prepare_for_state_elimination();
+        self.to_regex_with(&ToRegexOptions::default())
+    }
 
-        loop {
-            let mut current = START;
-            while current < automata.size && (automata.is_start_state(current) || automata.is_finite_state(current)) {
-                current += 1;
-            }
+    pub fn to_regex_in(&self, dialect: Dialect) -> Option<String> {
+        self.to_regex_with(&ToRegexOptions {
+            dialect,
+            ..Default::default()
+        })
+    }
 
-            if current == automata.size {
-                break match &automata.transition_matrix[START].last().unwrap() {
-                    Some(regex) => Some(format!("^{regex}$")),
-                    None => None,
-                };
-            }
+    // None stands for the empty language, which has no regex in most dialects
+    pub fn to_regex_with(&self, options: &ToRegexOptions) -> Option<String> {
+        limits::unlimited(|budget| self.try_to_regex_with(options, budget))
+    }
 
-            for incoming in automata.get_incoming_states(current) {
-                for outcoming in automata.get_outcoming_states(current) {
-                    automata.eliminate_transition(incoming, current, outcoming);
-                }
+    pub fn try_to_regex_with(
+        &self,
+        options: &ToRegexOptions,
+        budget: &Budget,
+    ) -> Result<Option<String>, String> {
+        let tree = self.try_to_regex_tree(options, budget)?;
+
+        Ok(tree.map(|tree| options.dialect.print_anchored(&tree)))
+    }
+
+    /// The regex before printing, so it is converted once for several dialects.
+    /// `options.dialect` is ignored.
+    pub fn try_to_regex_tree(
+        &self,
+        options: &ToRegexOptions,
+        budget: &Budget,
+    ) -> Result<Option<ast::Tree>, String> {
+        budget.check_matrix_size(self.size)?;
+
+        let regex = match options.method {
+            ConversionMethod::StateElimination => {
+                self.prepare_for_state_elimination().eliminate_states_in(
+                    options.order,
+                    options.search_width,
+                    budget,
+                    &mut |_, _, _| {},
+                )?
             }
+            ConversionMethod::Arden => self.solve_equations(budget)?,
+        };
+        let Some(regex) = regex else {
+            return Ok(None);
+        };
 
-            automata.eliminate_state(current);
+        // Every dialect prints ε as ^$
+        if regex.is_empty() {
+            return Ok(Some(ast::Tree::from_regex(&ast::EPSILON.to_string())));
+        }
+
+        let mut tree = ast::Tree::try_from_regex(&regex, budget)?;
+        if options.simplify {
+            let simplified = simplify::try_simplify(&tree, budget)?;
+            debug!(
+                "simplified regex of size {} to {}",
+                tree.size(),
+                simplified.size()
+            );
+            tree = simplified;
         }
+
+        Ok(Some(tree))
     }
 
     fn prepare_for_state_elimination(&self) -> Automata<String> {
@@ -154,12 +290,28 @@ impl Automata {
 }
 
 impl Automata<String> {
-    fn eliminate_transition(&mut self, incoming: usize, current: usize, outcoming: usize) {
+    fn eliminate_transition(
+        &mut self,
+        incoming: usize,
+        current: usize,
+        outcoming: usize,
+    ) -> TransitionStep {
         let former_regex_opt = &self.transition_matrix[incoming][outcoming];
         let incoming_regex = self.transition_matrix[incoming][current].as_ref().unwrap();
         let cyclic_regex_opt = &self.transition_matrix[current][current];
         let outcoming_regex = self.transition_matrix[current][outcoming].as_ref().unwrap();
 
+        let mut step = TransitionStep {
+            incoming,
+            outcoming,
+            former_regex: former_regex_opt.clone(),
+            incoming_regex: incoming_regex.clone(),
+            cyclic_regex: cyclic_regex_opt.clone(),
+            outcoming_regex: outcoming_regex.clone(),
+            result: String::new(),
+            is_unfolded: false,
+        };
+
         // Optimisations
         if Self::is_unfold_axiom_applicable(
             former_regex_opt,
@@ -167,11 +319,13 @@ impl Automata<String> {
             cyclic_regex_opt,
             outcoming_regex,
         ) {
-            self.transition_matrix[incoming][outcoming] = Some(format!("{}*", Self::wrap_if_needed(incoming_regex)));
-            return;
+            step.result = format!("{}*", Self::wrap_if_needed(incoming_regex));
+            step.is_unfolded = true;
+            self.transition_matrix[incoming][outcoming] = Some(step.result.clone());
+            return step;
         }
 
-        // TODO: idempotency, distributivity
+        // Idempotency, distributivity etc. are left to simplify::simplify
 
         // Common scenario
         let mut result = String::new();
@@ -203,35 +357,80 @@ impl Automata<String> {
         }
 
         if can_be_epsilon {
-            result = format!("{}?", Self::wrap_if_needed(&result));
+            result = Self::wrap(&format!("{result}|{}", ast::EPSILON));
         }
 
-        self.transition_matrix[incoming][outcoming] = Some(result);
+        self.transition_matrix[incoming][outcoming] = Some(result.clone());
+        step.result = result;
+
+        step
     }
 
     fn is_unfold_axiom_applicable(
         former_regex_opt: &Option<String>,
-        incoming_regex: &String,
+        incoming_regex: &str,
         cyclic_regex_opt: &Option<String>,
         outcoming_regex: &String,
     ) -> bool {
         *former_regex_opt == Some(EPSILON)
-            && Some(incoming_regex.clone()) == *cyclic_regex_opt
+            && cyclic_regex_opt.as_deref() == Some(incoming_regex)
             && *outcoming_regex == EPSILON
     }
 
     fn wrap_if_needed(regex: &String) -> String {
-        if regex.len() == 1 || regex.chars().next() == Some('(') && regex.chars().last() == Some(')') {
+        if regex.chars().count() == 1 || Self::is_wrapped(regex) {
             return regex.to_string()
         }
 
         Self::wrap(regex)
     }
 
+    // "(a)(b)" starts with '(' and ends with ')', but is not a single group
+    fn is_wrapped(regex: &str) -> bool {
+        if !regex.starts_with('(') || !regex.ends_with(')') {
+            return false;
+        }
+
+        let mut depth = 0;
+        for (i, symbol) in regex.char_indices() {
+            match symbol {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+
+            if depth == 0 {
+                return i == regex.len() - 1;
+            }
+        }
+
+        false
+    }
+
     fn wrap(regex: &String) -> String {
         format!("({regex})")
     }
 
+    // The regexes may grow fast, so the budget is checked for every transition
+    fn eliminate(
+        &mut self,
+        current: usize,
+        budget: &Budget,
+    ) -> Result<Vec<TransitionStep>, String> {
+        let mut steps = Vec::new();
+
+        for incoming in self.get_incoming_states(current) {
+            for outcoming in self.get_outcoming_states(current) {
+                budget.check_time()?;
+                steps.push(self.eliminate_transition(incoming, current, outcoming));
+            }
+        }
+
+        self.eliminate_state(current);
+
+        Ok(steps)
+    }
+
     fn eliminate_state(&mut self, i: usize) {
         self.start_states.swap_remove(i);
 
@@ -339,19 +538,39 @@ struct ComplexState {
 
 #[derive(Debug)]
 struct Details {
+    // The order of the state in the BFS until the states are numbered
     index: usize,
     is_finite: bool,
     incoming_states: Vec<ComplexState>,
 }
 
-const TEMPORARY_INDEX: usize = 0;
 const START_STATE: ComplexState = ComplexState {
     a1_index: START,
     symbol: '\0',
     a2_index: START,
 };
 
+/// Sizes of the product automata in `intersection_with_stats`.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct IntersectionStats {
+    pub states_before_remove_traps: usize,
+    pub states_after_remove_traps: usize,
+}
+
 pub fn intersection(a1: &Automata, a2: &Automata) -> Automata {
+    intersection_with_stats(a1, a2).0
+}
+
+pub fn intersection_with_stats(a1: &Automata, a2: &Automata) -> (Automata, IntersectionStats) {
+    limits::unlimited(|budget| try_intersection_with_stats(a1, a2, budget))
+}
+
+pub fn try_intersection_with_stats(
+    a1: &Automata,
+    a2: &Automata,
+    budget: &Budget,
+) -> Result<(Automata, IntersectionStats), String> {
     let mut state_details_map = HashMap::<ComplexState, Details>::new();
     state_details_map.insert(
         START_STATE,
@@ -362,23 +581,34 @@ pub fn intersection(a1: &Automata, a2: &Automata) -> Automata {
         },
     );
 
-    intersection_bfs(a1, a2, &mut state_details_map);
+    intersection_bfs(a1, a2, &mut state_details_map, budget)?;
+    let states_before_remove_traps = state_details_map.len();
     remove_traps(&mut state_details_map);
-
-    let mut size = 1;
-    for (state, details) in state_details_map.iter_mut() {
-        if state != &START_STATE {
-            details.index = size;
-            size += 1;
-        }
+    let stats = IntersectionStats {
+        states_before_remove_traps,
+        states_after_remove_traps: state_details_map.len(),
+    };
+
+    // States keep the order they were found in, so the automata does not depend on the hasher;
+    // START keeps its number even if it was a trap
+    let mut order: Vec<usize> = state_details_map
+        .values()
+        .map(|details| details.index)
+        .filter(|&index| index != START)
+        .collect();
+    order.push(START);
+    order.sort_unstable();
+    for details in state_details_map.values_mut() {
+        details.index = order.binary_search(&details.index).unwrap();
     }
-    
+    let size = order.len();
+
     let mut automata = Automata::<char>::new(size);
 
     for (state, details) in &state_details_map {
         let j = details.index;
         for incoming_state in &details.incoming_states {
-            let i = state_details_map[&incoming_state].index;
+            let i = state_details_map[incoming_state].index;
             automata.transition_matrix[i][j] = Some(state.symbol);
         }
     }
@@ -389,16 +619,24 @@ pub fn intersection(a1: &Automata, a2: &Automata) -> Automata {
         }
     }
 
-    automata
+    Ok((automata, stats))
 }
 
-fn intersection_bfs(a1: &Automata, a2: &Automata, state_details_map: &mut HashMap<ComplexState, Details>) {
+fn intersection_bfs(
+    a1: &Automata,
+    a2: &Automata,
+    state_details_map: &mut HashMap<ComplexState, Details>,
+    budget: &Budget,
+) -> Result<(), String> {
     let a2_transitions = a2.transform_transitions();
 
     let mut states_deq = VecDeque::<ComplexState>::new();
     states_deq.push_back(START_STATE);
 
     while let Some(state) = states_deq.pop_front() {
+        budget.check_time()?;
+        budget.check_product_states(state_details_map.len())?;
+
         let a1_row = &a1.transition_matrix[state.a1_index];
         for (a1_index, symbol_opt) in a1_row.iter().enumerate() {
             if symbol_opt.is_none() {
@@ -408,12 +646,13 @@ fn intersection_bfs(a1: &Automata, a2: &Automata, state_details_map: &mut HashMa
             let mut outcoming_indices = Vec::<(&char, &Vec<usize>)>::new();
 
             let symbol = &symbol_opt.unwrap();
-            if let Some(a2_indices) = a2_transitions[state.a2_index].get(&symbol) {
+            if let Some(a2_indices) = a2_transitions[state.a2_index].get(symbol) {
                 outcoming_indices.push((symbol, a2_indices));
             } else if symbol == &ARBITARY {
                 for (symbol, a2_indices) in &a2_transitions[state.a2_index] {
                     outcoming_indices.push((symbol, a2_indices));
                 }
+                outcoming_indices.sort_unstable_by_key(|&(symbol, _)| *symbol);
             }
 
             for (&symbol, a2_indices) in outcoming_indices {
@@ -427,10 +666,11 @@ fn intersection_bfs(a1: &Automata, a2: &Automata, state_details_map: &mut HashMa
                     if let Some(details) = state_details_map.get_mut(&outcoming_state) {
                         details.incoming_states.push(state.clone());
                     } else {
+                        let index = state_details_map.len();
                         state_details_map.insert(
                             outcoming_state.clone(),
                             Details {
-                                index: TEMPORARY_INDEX,
+                                index,
                                 is_finite: a1.finite_states[a1_index] && a2.finite_states[a2_index],
                                 incoming_states: vec![state.clone()],
                             },
@@ -442,6 +682,8 @@ fn intersection_bfs(a1: &Automata, a2: &Automata, state_details_map: &mut HashMa
             }
         }
     }
+
+    Ok(())
 }
 
 fn remove_traps(state_details_map: &mut HashMap<ComplexState, Details>) {
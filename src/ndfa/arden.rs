@@ -0,0 +1,161 @@
+use super::limits::Budget;
+use super::{ast, Automata, EPSILON, START};
+
+/*
+ * Brzozowski's algebraic method. Every state i gets the equation
+ *
+ *     X_i = Σ a_ij X_j + b_i,    b_i = ε if i is final and ∅ otherwise,
+ *
+ * where X_i is the language accepted from i. Equations are solved from the
+ * last one to X_START by Arden's lemma: X = aX + b has the solution X = a*b.
+ */
+
+// None stands for ∅, EPSILON for ε
+struct Equation {
+    coefficients: Vec<Option<String>>,
+    constant: Option<String>,
+}
+
+impl Automata {
+    // Fails if the budget runs out before an equation is solved
+    pub(super) fn solve_equations(&self, budget: &Budget) -> Result<Option<String>, String> {
+        let mut equations: Vec<Equation> = (0..self.size)
+            .map(|i| Equation {
+                coefficients: self.transition_matrix[i]
+                    .iter()
+                    .map(|symbol_opt| symbol_opt.map(|symbol| symbol.to_string()))
+                    .collect(),
+                constant: self.is_finite_state(i).then_some(EPSILON),
+            })
+            .collect();
+
+        for i in (0..self.size).rev() {
+            budget.check_time()?;
+            apply_arden_lemma(&mut equations[i], i);
+
+            if i == START {
+                break;
+            }
+
+            for k in 0..i {
+                budget.check_time()?;
+                substitute(&mut equations, i, k);
+            }
+        }
+
+        Ok(equations[START].constant.take())
+    }
+}
+
+// X_i = a X_i + Σ b_j X_j + c  =>  X_i = Σ a*b_j X_j + a*c
+fn apply_arden_lemma(equation: &mut Equation, i: usize) {
+    let Some(cyclic) = equation.coefficients[i].take() else {
+        return;
+    };
+
+    let starred = star(&cyclic);
+
+    for coefficient in equation.coefficients.iter_mut().flatten() {
+        *coefficient = concat(&starred, coefficient);
+    }
+
+    if let Some(constant) = &equation.constant {
+        equation.constant = Some(concat(&starred, constant));
+    }
+}
+
+// Replaces X_i in the k-th equation with the right side of the i-th one
+fn substitute(equations: &mut [Equation], i: usize, k: usize) {
+    let Some(coefficient) = equations[k].coefficients[i].take() else {
+        return;
+    };
+
+    for j in 0..equations[i].coefficients.len() {
+        if let Some(other) = &equations[i].coefficients[j] {
+            let term = concat(&coefficient, other);
+            equations[k].coefficients[j] = union(&equations[k].coefficients[j], term);
+        }
+    }
+
+    if let Some(constant) = &equations[i].constant {
+        let term = concat(&coefficient, constant);
+        equations[k].constant = union(&equations[k].constant, term);
+    }
+}
+
+fn union(lhs: &Option<String>, rhs: String) -> Option<String> {
+    match lhs {
+        None => Some(rhs),
+        Some(lhs) if *lhs == rhs => Some(rhs),
+        Some(lhs) => Some(format!("({}|{})", as_operand(lhs), as_operand(&rhs))),
+    }
+}
+
+fn concat(lhs: &str, rhs: &str) -> String {
+    format!("{lhs}{rhs}")
+}
+
+fn star(regex: &str) -> String {
+    if regex.is_empty() {
+        return EPSILON;
+    }
+
+    format!(
+        "{}*",
+        Automata::<String>::wrap_if_needed(&regex.to_string())
+    )
+}
+
+fn as_operand(regex: &str) -> String {
+    if regex.is_empty() {
+        ast::EPSILON.to_string()
+    } else {
+        regex.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ndfa::dfa::are_equivalent;
+    use crate::ndfa::{Automata, ConversionMethod, ToRegexOptions};
+
+    fn convert(regex: &str, method: ConversionMethod) -> Automata {
+        let automata = Automata::from_regex(regex);
+        let options = ToRegexOptions {
+            method,
+            ..Default::default()
+        };
+
+        Automata::from_printed_regex(&automata.to_regex_with(&options).unwrap())
+    }
+
+    #[test]
+    fn methods_agree() {
+        for regex in [
+            "a",
+            "ab*c",
+            "(a|b)*abb",
+            "(ab|ba)*(a|ε)",
+            "a(b|c)*|ca*",
+            "(a*b*)*c",
+        ] {
+            let original = Automata::from_regex(regex);
+            let by_elimination = convert(regex, ConversionMethod::StateElimination);
+            let by_arden = convert(regex, ConversionMethod::Arden);
+
+            assert!(are_equivalent(&original, &by_elimination), "{regex}");
+            assert!(are_equivalent(&original, &by_arden), "{regex}");
+        }
+    }
+
+    #[test]
+    fn distinguishing_word() {
+        let a1 = Automata::from_regex("(a|b)*");
+        let a2 = Automata::from_regex("(a|b)*b|ε");
+
+        assert_eq!(
+            crate::ndfa::dfa::find_distinguishing_word(&a1, &a2),
+            Some("a".to_string())
+        );
+    }
+}
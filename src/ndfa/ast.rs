@@ -1,7 +1,10 @@
 use std::iter::Peekable;
 use std::str::Chars;
 
+use super::limits::{self, Budget};
+
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tree {
     pub root: Union,
     pub linearized_symbols: usize,
@@ -10,36 +13,46 @@ pub struct Tree {
 /*
  * <Union> ::= <Concat> ('|' <Concat>)*
  *
- * <Concat> ::= <Basic> (<Basic>)*
+ * <Concat> ::= (<Basic>)*
  *
  * <Basic> ::= <Atomic> ('*')?
  *
- * <Atomic> ::= CHAR | '^' | '$' | '.' | '(' <Union> ')'
+ * <Atomic> ::= CHAR | 'ε' | '^' | '$' | '.' | '(' <Union> ')'
+ *
+ * An empty <Concat> (as in `a|` or `(|b)`) stands for ε.
 */
 
-#[derive(Debug)]
+pub const EPSILON: char = 'ε';
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Union {
     pub concats: Vec<Concat>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Concat {
     pub basics: Vec<Basic>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Basic {
     pub atomic: Atomic,
     pub is_iter: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Atomic {
     LinearizedSymbol(LinearizedSymbol),
+    Epsilon,
     Union(Union),
 }
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LinearizedSymbol {
     pub symbol: char,
     pub index: usize,
@@ -47,12 +60,17 @@ pub struct LinearizedSymbol {
 
 impl Tree {
     pub fn from_regex(regex: &str) -> Self {
+        limits::unlimited(|budget| Self::try_from_regex(regex, budget))
+    }
+
+    /// The budget is checked for every group.
+    pub fn try_from_regex(regex: &str, budget: &Budget) -> Result<Self, String> {
         assert!(!regex.is_empty());
 
         let mut tree = Self::default();
-        tree.initialize(regex);
+        tree.initialize(regex, budget)?;
 
-        tree
+        Ok(tree)
     }
 
     fn default() -> Self {
@@ -62,35 +80,46 @@ impl Tree {
         }
     }
 
-    fn initialize(&mut self, regex: &str) {
+    fn initialize(&mut self, regex: &str, budget: &Budget) -> Result<(), String> {
         let mut stream = regex.chars().peekable();
 
-        self.root = self.parse_union(&mut stream);
+        self.root = self.parse_union(&mut stream, budget)?;
+        Ok(())
     }
 
-    fn parse_union(&mut self, stream: &mut Peekable<Chars<'_>>) -> Union {
-        let mut union = Union::new(vec![self.parse_concat(stream)]);
+    fn parse_union(
+        &mut self,
+        stream: &mut Peekable<Chars<'_>>,
+        budget: &Budget,
+    ) -> Result<Union, String> {
+        budget.check_time()?;
+
+        let mut union = Union::new(vec![self.parse_concat(stream, budget)?]);
 
         while stream.peek() == Some(&'|') {
             stream.next();
-            union.concats.push(self.parse_concat(stream));
+            union.concats.push(self.parse_concat(stream, budget)?);
         }
 
-        union
+        Ok(union)
     }
 
-    fn parse_concat(&mut self, stream: &mut Peekable<Chars<'_>>) -> Concat {
-        let mut concat = Concat::new(vec![self.parse_basic(stream)]);
+    fn parse_concat(
+        &mut self,
+        stream: &mut Peekable<Chars<'_>>,
+        budget: &Budget,
+    ) -> Result<Concat, String> {
+        let mut concat = Concat::new(Vec::new());
 
         while let Some(symbol) = stream.peek() {
             if !Self::is_atomic_start(*symbol) {
                 break;
             }
 
-            concat.basics.push(self.parse_basic(stream));
+            concat.basics.push(self.parse_basic(stream, budget)?);
         }
 
-        concat
+        Ok(concat)
     }
 
     fn is_atomic_start(symbol: char) -> bool {
@@ -101,29 +130,44 @@ impl Tree {
             || symbol == '('
     }
 
-    fn parse_basic(&mut self, stream: &mut Peekable<Chars<'_>>) -> Basic {
-        let mut basic = Basic::new(self.parse_atomic(stream), false);
+    fn parse_basic(
+        &mut self,
+        stream: &mut Peekable<Chars<'_>>,
+        budget: &Budget,
+    ) -> Result<Basic, String> {
+        let mut basic = Basic::new(self.parse_atomic(stream, budget)?, false);
 
         if stream.peek() == Some(&'*') {
             stream.next();
             basic.is_iter = true;
         }
 
-        basic
+        Ok(basic)
     }
 
-    fn parse_atomic(&mut self, stream: &mut Peekable<Chars<'_>>) -> Atomic {
+    fn parse_atomic(
+        &mut self,
+        stream: &mut Peekable<Chars<'_>>,
+        budget: &Budget,
+    ) -> Result<Atomic, String> {
         let symbol = stream.next().unwrap();
 
         if symbol == '(' {
-            let atomic = Atomic::Union(self.parse_union(stream));
+            let atomic = Atomic::Union(self.parse_union(stream, budget)?);
 
             assert_eq!(stream.next(), Some(')'));
-            return atomic;
+            return Ok(atomic);
+        }
+
+        if symbol == EPSILON {
+            return Ok(Atomic::Epsilon);
         }
 
         self.linearized_symbols += 1;
-        Atomic::LinearizedSymbol(LinearizedSymbol::new(symbol, self.linearized_symbols))
+        Ok(Atomic::LinearizedSymbol(LinearizedSymbol::new(
+            symbol,
+            self.linearized_symbols,
+        )))
     }
 
     // First-set
@@ -163,6 +207,7 @@ impl Tree {
     fn get_first_of_atomic(atomic: &Atomic) -> Vec<LinearizedSymbol> {
         match atomic {
             Atomic::LinearizedSymbol(linearized_symbol) => vec![*linearized_symbol],
+            Atomic::Epsilon => Vec::new(),
             Atomic::Union(union) => Self::get_first_of_union(union),
         }
     }
@@ -204,6 +249,7 @@ impl Tree {
     fn get_last_of_atomic(atomic: &Atomic) -> Vec<LinearizedSymbol> {
         match atomic {
             Atomic::LinearizedSymbol(linearized_symbol) => vec![*linearized_symbol],
+            Atomic::Epsilon => Vec::new(),
             Atomic::Union(union) => Self::get_last_of_union(union),
         }
     }
@@ -233,7 +279,7 @@ impl Tree {
             follow_set.extend(Self::get_follow_of_basic(basic));
         }
     
-        for i in 0..basics.len() - 1 {
+        for i in 0..basics.len().saturating_sub(1) {
             for j in (i + 1)..basics.len() {
                 follow_set.extend(Self::get_cartesian_product(
                     &Self::get_last_of_basic(&basics[i]),
@@ -282,6 +328,7 @@ impl Tree {
     fn get_follow_of_atomic(atomic_exp: &Atomic) -> Vec<(LinearizedSymbol, LinearizedSymbol)> {
         match atomic_exp {
             Atomic::LinearizedSymbol(_linearized_symbol) => Vec::new(),
+            Atomic::Epsilon => Vec::new(),
             Atomic::Union(union) => Self::get_follow_of_union(union),
         }
     }
@@ -294,7 +341,7 @@ impl Tree {
     
     fn does_epsilon_satisfy_union(union: &Union) -> bool {
         for concat in &union.concats {
-            if Self::does_epsilon_satisfy_concat(&concat) {
+            if Self::does_epsilon_satisfy_concat(concat) {
                 return true;
             }
         }
@@ -319,17 +366,46 @@ impl Tree {
     fn does_epsilon_satisfy_atomic(atomic: &Atomic) -> bool {
         match atomic {
             Atomic::LinearizedSymbol(_linearized_symbol) => false,
+            Atomic::Epsilon => true,
             Atomic::Union(union) => Self::does_epsilon_satisfy_union(union),
         }
     }
+
+    // Linearized symbols
+
+    /// Symbols in the order of their indices, which are the states of `Automata::from_regex`.
+    pub fn get_linearized_symbols(&self) -> Vec<LinearizedSymbol> {
+        let mut symbols = Vec::new();
+        Self::collect_linearized_symbols(&self.root, &mut symbols);
+
+        symbols
+    }
+
+    pub fn get_linearized_symbol(&self, index: usize) -> Option<LinearizedSymbol> {
+        self.get_linearized_symbols()
+            .into_iter()
+            .find(|symbol| symbol.index == index)
+    }
+
+    fn collect_linearized_symbols(union: &Union, symbols: &mut Vec<LinearizedSymbol>) {
+        for basic in union.concats.iter().flat_map(|concat| &concat.basics) {
+            match &basic.atomic {
+                Atomic::LinearizedSymbol(linearized_symbol) => symbols.push(*linearized_symbol),
+                Atomic::Epsilon => {}
+                Atomic::Union(union) => Self::collect_linearized_symbols(union, symbols),
+            }
+        }
+    }
 }
 
 impl Union {
     pub fn new(concats: Vec<Concat>) -> Self {
         Self { concats }
     }
+}
 
-    pub fn default() -> Self {
+impl Default for Union {
+    fn default() -> Self {
         Self::new(Vec::<Concat>::new())
     }
 }
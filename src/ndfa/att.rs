@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::nfa::Nfa;
+use super::{Automata, ARBITARY, START};
+
+/*
+ * AT&T tabular format of OpenFst acceptors:
+ *
+ * 0 1 a            src dst label, the first state of the first line is the start state
+ * 1 1 <sigma> 0.5  src dst label weight, weights are ignored
+ * 1 2 b b          src dst input output [weight] of a transducer, the labels must match
+ * 1                final state, an optional weight is ignored
+ *
+ * A 4th field is the output label if it equals the label or is not a number.
+ * State ids may be any numbers, they are numbered densely in the order they appear.
+ *
+ * Labels are symbol names, or numeric ids if a symbol table is given.
+ * The '.' wildcard is named <sigma>, ε is <eps> and has id 0.
+ */
+
+const EPSILON_NAME: &str = "<eps>";
+const ARBITARY_NAME: &str = "<sigma>";
+// Whitespace separates the fields
+const SPACE_NAME: &str = "<space>";
+
+const ERR_INVALID_LINE: &str = "invalid line in fsm file";
+const ERR_INVALID_STATE: &str = "invalid state in fsm file";
+const ERR_INVALID_WEIGHT: &str = "invalid weight in fsm file";
+const ERR_NOT_ACCEPTOR: &str = "fsm is a transducer, input and output labels differ";
+const ERR_UNKNOWN_SYMBOL: &str = "unknown symbol in fsm file";
+const ERR_INVALID_SYMBOL_TABLE: &str = "invalid symbol table";
+
+/// Ids of the symbol names, the id is the index of the name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolTable {
+    names: Vec<String>,
+}
+
+impl SymbolTable {
+    pub fn from_automata(automata: &Automata) -> Self {
+        let mut symbols: Vec<char> = automata
+            .transition_matrix
+            .iter()
+            .flatten()
+            .flatten()
+            .copied()
+            .filter(|&symbol| symbol != ARBITARY)
+            .collect();
+        symbols.sort_unstable();
+        symbols.dedup();
+
+        let mut names = vec![EPSILON_NAME.to_string(), ARBITARY_NAME.to_string()];
+        names.extend(symbols.into_iter().map(get_name));
+
+        Self { names }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut names = Vec::<String>::new();
+
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [name, id] = fields.as_slice() else {
+                return Err(ERR_INVALID_SYMBOL_TABLE.to_string());
+            };
+            let id: usize = id.parse().map_err(|_| ERR_INVALID_SYMBOL_TABLE)?;
+
+            if names.len() <= id {
+                names.resize(id + 1, String::new());
+            }
+            names[id] = name.to_string();
+        }
+
+        Ok(Self { names })
+    }
+
+    pub fn to_text(&self) -> String {
+        self.names
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| !name.is_empty())
+            .map(|(id, name)| format!("{name}\t{id}\n"))
+            .collect()
+    }
+
+    fn get_id(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    fn get_name(&self, id: &str) -> Option<&str> {
+        let id: usize = id.parse().ok()?;
+
+        self.names
+            .get(id)
+            .map(String::as_str)
+            .filter(|name| !name.is_empty())
+    }
+}
+
+impl Automata {
+    pub fn from_att(fsm: &str, symbols: Option<&SymbolTable>) -> Result<Self, String> {
+        let mut arcs = Vec::<(usize, usize, &str)>::new();
+        let mut finite_states = Vec::<usize>::new();
+
+        // State ids are numbered in the order they appear, so any id fits
+        let mut states = HashMap::<usize, usize>::new();
+        let mut get_state = |state: &str| -> Result<usize, String> {
+            let index = states.len();
+            Ok(*states.entry(parse_state(state)?).or_insert(index))
+        };
+
+        for line in fsm.lines().filter(|line| !line.trim().is_empty()) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            match fields.as_slice() {
+                [state] => finite_states.push(get_state(state)?),
+                [state, weight] => {
+                    finite_states.push(get_state(state)?);
+                    parse_weight(weight)?;
+                }
+                [from, to, label, rest @ ..] if rest.len() <= 2 => {
+                    let output = match rest {
+                        [field] if field == label || field.parse::<f64>().is_err() => Some(field),
+                        [weight] => {
+                            parse_weight(weight)?;
+                            None
+                        }
+                        [output, weight] => {
+                            parse_weight(weight)?;
+                            Some(output)
+                        }
+                        _ => None,
+                    };
+
+                    if output.is_some_and(|output| output != label) {
+                        return Err(ERR_NOT_ACCEPTOR.to_string());
+                    }
+
+                    let label = match symbols {
+                        Some(symbols) => symbols.get_name(label).ok_or(ERR_UNKNOWN_SYMBOL)?,
+                        None => label,
+                    };
+
+                    arcs.push((get_state(from)?, get_state(to)?, label));
+                }
+                _ => return Err(ERR_INVALID_LINE.to_string()),
+            }
+        }
+
+        // The first line starts with the start state
+        let size = states.len();
+        if size == 0 {
+            return Ok(Self::new_empty());
+        }
+
+        let mut nfa = Nfa::new(size, 0);
+
+        for state in finite_states {
+            nfa.set_finite(state);
+        }
+
+        for (from, to, label) in arcs {
+            match get_symbol(label)? {
+                Some(symbol) => nfa.add_transition(from, to, &symbol.to_string()),
+                None => nfa.add_transition(from, to, ""),
+            }
+        }
+
+        Ok(nfa.to_automata())
+    }
+
+    pub fn read_att(path: &Path, symbols_path: Option<&Path>) -> Result<Self, String> {
+        let symbols = match symbols_path {
+            Some(symbols_path) => Some(SymbolTable::parse(
+                &fs::read_to_string(symbols_path).map_err(|e| e.to_string())?,
+            )?),
+            None => None,
+        };
+
+        Self::from_att(
+            &fs::read_to_string(path).map_err(|e| e.to_string())?,
+            symbols.as_ref(),
+        )
+    }
+
+    /// Labels are written as ids of `symbols` if it is given and as names otherwise.
+    pub fn to_att(&self, symbols: Option<&SymbolTable>) -> String {
+        let mut fsm = String::new();
+
+        // Nothing but the start state is reachable then, so it goes first
+        let has_arcs = self.transition_matrix[START].iter().any(Option::is_some);
+
+        for (i, row) in self.transition_matrix.iter().enumerate() {
+            if !has_arcs {
+                break;
+            }
+
+            for (j, symbol_opt) in row.iter().enumerate() {
+                if let Some(symbol) = symbol_opt {
+                    let name = get_name(*symbol);
+                    let label = match symbols.and_then(|symbols| symbols.get_id(&name)) {
+                        Some(id) => id.to_string(),
+                        None => name,
+                    };
+
+                    fsm.push_str(&format!("{i}\t{j}\t{label}\n"));
+                }
+            }
+        }
+
+        for i in 0..self.size {
+            if self.is_finite_state(i) && (has_arcs || i == START) {
+                fsm.push_str(&format!("{i}\n"));
+            }
+        }
+
+        fsm
+    }
+
+    /// The symbol table is written to `symbols_path` and the labels are ids then.
+    pub fn write_att(&self, path: &Path, symbols_path: Option<&Path>) -> Result<(), String> {
+        let symbols = symbols_path.map(|_| SymbolTable::from_automata(self));
+
+        if let (Some(path), Some(symbols)) = (symbols_path, &symbols) {
+            fs::write(path, symbols.to_text()).map_err(|e| e.to_string())?;
+        }
+
+        fs::write(path, self.to_att(symbols.as_ref())).map_err(|e| e.to_string())
+    }
+}
+
+fn parse_state(state: &str) -> Result<usize, String> {
+    state.parse().map_err(|_| ERR_INVALID_STATE.to_string())
+}
+
+fn parse_weight(weight: &str) -> Result<(), String> {
+    weight
+        .parse::<f64>()
+        .map(|_| ())
+        .map_err(|_| ERR_INVALID_WEIGHT.to_string())
+}
+
+fn get_name(symbol: char) -> String {
+    match symbol {
+        ARBITARY => ARBITARY_NAME.to_string(),
+        ' ' => SPACE_NAME.to_string(),
+        symbol => symbol.to_string(),
+    }
+}
+
+// None stands for ε
+fn get_symbol(name: &str) -> Result<Option<char>, String> {
+    match name {
+        EPSILON_NAME => Ok(None),
+        ARBITARY_NAME => Ok(Some(ARBITARY)),
+        SPACE_NAME => Ok(Some(' ')),
+        _ => {
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(symbol), None) => Ok(Some(symbol)),
+                _ => Err(ERR_UNKNOWN_SYMBOL.to_string()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SymbolTable;
+    use crate::ndfa::dfa::are_equivalent;
+    use crate::ndfa::{self, Automata};
+
+    #[test]
+    fn round_trip() {
+        for regex in ["a", "(a|b)*abb", "ab*c|ε", "a.b", ""] {
+            let automata = Automata::from_regex(regex);
+            let symbols = SymbolTable::from_automata(&automata);
+
+            let by_names = Automata::from_att(&automata.to_att(None), None).unwrap();
+            let by_ids = Automata::from_att(
+                &automata.to_att(Some(&symbols)),
+                Some(&SymbolTable::parse(&symbols.to_text()).unwrap()),
+            )
+            .unwrap();
+
+            assert!(are_equivalent(&automata, &by_names), "{regex}");
+            assert!(are_equivalent(&automata, &by_ids), "{regex}");
+        }
+
+        assert!(Automata::from_att("", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn operations_on_loaded() {
+        // 0 -a-> 1 -<eps>-> 2, 2 -b-> 2 and 0 is not the smallest state
+        let a1 = Automata::from_att("3 1 a\n1 2 <eps>\n2 2 b\n2 0.5\n", None).unwrap();
+        let a2 = Automata::from_att("0 0 a\n0 0 b\n0\n", None).unwrap();
+
+        assert!(are_equivalent(&a1, &Automata::from_regex("ab*")));
+        assert!(are_equivalent(
+            &ndfa::intersection(&a1, &Automata::from_regex("(a|b)*b")),
+            &Automata::from_regex("abb*")
+        ));
+        assert!(are_equivalent(
+            &ndfa::union(&a1, &a2),
+            &Automata::from_regex("(a|b)*")
+        ));
+    }
+
+    #[test]
+    fn weighted() {
+        // Acceptor arcs have 4 fields with a weight, transducer arcs have 5
+        let acceptor = Automata::from_att("0 1 a 0.5\n1 1 b 1\n1 2.5\n", None).unwrap();
+        let transducer = Automata::from_att("0 1 a a 0\n1 1 b b 0.25\n1\n", None).unwrap();
+
+        assert!(are_equivalent(&acceptor, &Automata::from_regex("ab*")));
+        assert!(are_equivalent(&transducer, &Automata::from_regex("ab*")));
+
+        // As fstprint writes a transducer, the 4th field is the output label then
+        let unweighted = Automata::from_att("0 1 a a\n1 1 b b\n1\n", None).unwrap();
+        let symbols = SymbolTable::parse("<eps> 0\n<sigma> 1\na 2\nb 3\n").unwrap();
+        let by_ids = Automata::from_att("0 1 2 2\n1 1 3 3\n1\n", Some(&symbols)).unwrap();
+
+        assert!(are_equivalent(&unweighted, &Automata::from_regex("ab*")));
+        assert!(are_equivalent(&by_ids, &Automata::from_regex("ab*")));
+    }
+
+    #[test]
+    fn states() {
+        // The start state is on the first line, whatever the line is
+        let final_first = Automata::from_att("1\n0 1 a\n1 1 b\n", None).unwrap();
+        let huge_ids = Automata::from_att("7 99999999999 a\n99999999999\n", None).unwrap();
+
+        assert!(are_equivalent(&final_first, &Automata::from_regex("b*")));
+        assert!(are_equivalent(&huge_ids, &Automata::from_regex("a")));
+        assert_eq!(huge_ids.size, 2);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Automata::from_att("0 1 a b\n", None).is_err());
+        assert!(Automata::from_att("0 1 a b 0.5\n", None).is_err());
+        assert!(Automata::from_att("0 1 a a x\n", None).is_err());
+        assert!(Automata::from_att("0 1 a\n1 x\n", None).is_err());
+        assert!(Automata::from_att("0 1 a a 0.5 1\n", None).is_err());
+        assert!(Automata::from_att("0 1 ab\n", None).is_err());
+        assert!(Automata::from_att("0 x a\n", None).is_err());
+        assert!(
+            Automata::from_att("0 1 7\n", Some(&SymbolTable::parse("<eps> 0").unwrap())).is_err()
+        );
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use super::limits::{self, Budget};
+use super::printer::Dialect;
+use super::{Automata, ToRegexOptions, ARBITARY, START};
+
+/// Complete deterministic automaton over the symbols of `alphabet` and one more
+/// symbol class, which stands for every symbol outside of it.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dfa {
+    pub alphabet: Vec<char>,
+    pub transitions: Vec<Vec<usize>>,
+    pub finite_states: Vec<bool>,
+}
+
+impl Dfa {
+    pub fn from_automata(automata: &Automata, alphabet: &[char]) -> Self {
+        limits::unlimited(|budget| Self::try_from_automata(automata, alphabet, budget))
+    }
+
+    pub fn try_from_automata(
+        automata: &Automata,
+        alphabet: &[char],
+        budget: &Budget,
+    ) -> Result<Self, String> {
+        let classes_count = alphabet.len() + 1;
+
+        let mut dfa = Self {
+            alphabet: alphabet.to_vec(),
+            transitions: Vec::new(),
+            finite_states: Vec::new(),
+        };
+
+        // Rows of the matrix are mostly empty, so the transitions are gathered once
+        let successors: Vec<Vec<(usize, char)>> = automata
+            .transition_matrix
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .filter_map(|(next_state, symbol_opt)| symbol_opt.map(|s| (next_state, s)))
+                    .collect()
+            })
+            .collect();
+
+        let mut indices = HashMap::<Vec<usize>, usize>::new();
+        let mut states_deq = VecDeque::<Vec<usize>>::new();
+
+        let start = vec![START];
+        indices.insert(
+            start.clone(),
+            dfa.add_state(automata, &start, classes_count),
+        );
+        states_deq.push_back(start);
+
+        while let Some(states) = states_deq.pop_front() {
+            budget.check_time()?;
+            let from = indices[&states];
+
+            for class in 0..classes_count {
+                let mut next_states = Vec::new();
+                for &state in &states {
+                    for &(next_state, symbol) in &successors[state] {
+                        if symbol == ARBITARY || dfa.get_class(symbol) == class {
+                            next_states.push(next_state);
+                        }
+                    }
+                }
+                next_states.sort_unstable();
+                next_states.dedup();
+
+                let to = match indices.get(&next_states) {
+                    Some(&to) => to,
+                    None => {
+                        let to = dfa.add_state(automata, &next_states, classes_count);
+                        indices.insert(next_states.clone(), to);
+                        states_deq.push_back(next_states);
+                        to
+                    }
+                };
+
+                dfa.transitions[from][class] = to;
+            }
+        }
+
+        Ok(dfa)
+    }
+
+    /// Merges the equivalent states by Moore's partition refinement, the start state stays 0.
+    pub fn minimize(&self) -> Self {
+        let mut classes: Vec<usize> = self.finite_states.iter().map(|&f| f as usize).collect();
+        let mut classes_count = 0;
+
+        // A refinement never merges classes, so the same count means the same partition
+        loop {
+            let mut indices = HashMap::<(usize, Vec<usize>), usize>::new();
+            let next_classes: Vec<usize> = (0..self.size())
+                .map(|state| {
+                    let signature = (
+                        classes[state],
+                        self.transitions[state]
+                            .iter()
+                            .map(|&to| classes[to])
+                            .collect(),
+                    );
+                    let index = indices.len();
+                    *indices.entry(signature).or_insert(index)
+                })
+                .collect();
+
+            let is_stable = indices.len() == classes_count;
+            classes_count = indices.len();
+            classes = next_classes;
+
+            if is_stable {
+                break;
+            }
+        }
+
+        let mut minimal = Self {
+            alphabet: self.alphabet.clone(),
+            transitions: vec![Vec::new(); classes_count],
+            finite_states: vec![false; classes_count],
+        };
+
+        for (state, &class) in classes.iter().enumerate() {
+            minimal.transitions[class] = self.transitions[state]
+                .iter()
+                .map(|&to| classes[to])
+                .collect();
+            minimal.finite_states[class] = self.finite_states[state];
+        }
+
+        minimal
+    }
+
+    /// Transition table, the start state is marked with `->` and the final ones with `*`.
+    pub fn to_text(&self) -> String {
+        let mut symbols: Vec<String> = self.alphabet.iter().map(char::to_string).collect();
+        symbols.push("other".to_string());
+
+        let mut text = format!("state\t{}\n", symbols.join("\t"));
+
+        for (state, row) in self.transitions.iter().enumerate() {
+            let start_mark = if state == START { "->" } else { "" };
+            let finite_mark = if self.finite_states[state] { "*" } else { "" };
+            let targets: Vec<String> = row.iter().map(|to| format!("q{to}")).collect();
+
+            text.push_str(&format!(
+                "{start_mark}{finite_mark}q{state}\t{}\n",
+                targets.join("\t")
+            ));
+        }
+
+        text
+    }
+
+    pub fn size(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn get_class(&self, symbol: char) -> usize {
+        self.alphabet
+            .iter()
+            .position(|&s| s == symbol)
+            .unwrap_or(self.alphabet.len())
+    }
+
+    // A symbol for the class of symbols outside of the alphabet, None if it is empty
+    pub fn get_other_symbol(&self) -> Option<char> {
+        ('a'..='z')
+            .chain('0'..='9')
+            .chain('A'..='Z')
+            .chain('!'..='~')
+            .chain(char::MIN..=char::MAX)
+            .find(|symbol| !self.alphabet.contains(symbol))
+    }
+
+    pub fn get_symbol(&self, class: usize) -> Option<char> {
+        match self.alphabet.get(class) {
+            Some(&symbol) => Some(symbol),
+            None => self.get_other_symbol(),
+        }
+    }
+
+    fn add_state(&mut self, automata: &Automata, states: &[usize], classes_count: usize) -> usize {
+        self.transitions.push(vec![0; classes_count]);
+        self.finite_states
+            .push(states.iter().any(|&state| automata.is_finite_state(state)));
+
+        self.transitions.len() - 1
+    }
+}
+
+// Explicit symbols of the automata, the wildcard is covered by the extra class
+pub fn get_alphabet(automatas: &[&Automata]) -> Vec<char> {
+    let mut alphabet = Vec::new();
+
+    for automata in automatas {
+        for symbol in automata.transition_matrix.iter().flatten().flatten() {
+            if *symbol != ARBITARY && !alphabet.contains(symbol) {
+                alphabet.push(*symbol);
+            }
+        }
+    }
+
+    alphabet.sort_unstable();
+    alphabet
+}
+
+/// The shortest word accepted by exactly one of the automata, None if they are equivalent.
+pub fn find_distinguishing_word(a1: &Automata, a2: &Automata) -> Option<String> {
+    limits::unlimited(|budget| try_find_distinguishing_word(a1, a2, budget))
+}
+
+pub fn try_find_distinguishing_word(
+    a1: &Automata,
+    a2: &Automata,
+    budget: &Budget,
+) -> Result<Option<String>, String> {
+    let alphabet = get_alphabet(&[a1, a2]);
+    let d1 = Dfa::try_from_automata(a1, &alphabet, budget)?;
+    let d2 = Dfa::try_from_automata(a2, &alphabet, budget)?;
+
+    let mut parents = HashMap::<(usize, usize), Option<((usize, usize), char)>>::new();
+    let mut states_deq = VecDeque::<(usize, usize)>::new();
+    parents.insert((START, START), None);
+    states_deq.push_back((START, START));
+
+    while let Some(state) = states_deq.pop_front() {
+        if d1.finite_states[state.0] != d2.finite_states[state.1] {
+            let mut word = Vec::new();
+            let mut current = state;
+            while let Some((parent, symbol)) = parents[&current] {
+                word.push(symbol);
+                current = parent;
+            }
+
+            return Ok(Some(word.into_iter().rev().collect()));
+        }
+
+        // No word goes through a class without symbols
+        for (class, symbol) in
+            (0..=alphabet.len()).filter_map(|class| Some((class, d1.get_symbol(class)?)))
+        {
+            let next = (
+                d1.transitions[state.0][class],
+                d2.transitions[state.1][class],
+            );
+            if let Entry::Vacant(entry) = parents.entry(next) {
+                entry.insert(Some((state, symbol)));
+                states_deq.push_back(next);
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+pub fn are_equivalent(a1: &Automata, a2: &Automata) -> bool {
+    find_distinguishing_word(a1, a2).is_none()
+}
+
+/// Converts the automata to a regex in the course notation and reads it back, the
+/// shortest word of exactly one of their languages is returned, None if the regex
+/// describes the automata.
+pub fn find_round_trip_word(
+    automata: &Automata,
+    options: &ToRegexOptions,
+    budget: &Budget,
+) -> Result<Option<String>, String> {
+    let regex = automata.try_to_regex_with(
+        &ToRegexOptions {
+            dialect: Dialect::Course,
+            ..options.clone()
+        },
+        budget,
+    )?;
+    let printed = match regex {
+        Some(regex) => Automata::try_from_printed_regex(&regex, budget)?,
+        None => Automata::new_empty(),
+    };
+
+    try_find_distinguishing_word(automata, &printed, budget)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_distinguishing_word, find_round_trip_word, get_alphabet, Dfa};
+    use crate::ndfa::elimination::EliminationOrder;
+    use crate::ndfa::limits::{self, Budget, Limits};
+    use crate::ndfa::{Automata, ConversionMethod, ToRegexOptions, START};
+
+    #[test]
+    fn minimize() {
+        // Subsets {0}, {1, 2}, {1, 3}, ... of (a|b)*abb collapse into 4 states and the trap
+        let automata = Automata::from_regex("(a|b)*abb");
+        let dfa = Dfa::from_automata(&automata, &get_alphabet(&[&automata]));
+        let minimal = dfa.minimize();
+
+        assert_eq!(minimal.size(), 5);
+        assert_eq!(minimal.finite_states.iter().filter(|&&f| f).count(), 1);
+        assert_eq!(minimal.minimize().size(), minimal.size());
+
+        let automata = Automata::from_regex("a*|a*a");
+        let minimal = Dfa::from_automata(&automata, &get_alphabet(&[&automata])).minimize();
+        assert_eq!(minimal.size(), 2);
+        assert_eq!(minimal.transitions[0], vec![0, 1]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let methods = [
+            (ConversionMethod::StateElimination, EliminationOrder::Index),
+            (ConversionMethod::StateElimination, EliminationOrder::Search),
+            (ConversionMethod::Arden, EliminationOrder::Index),
+        ];
+
+        for regex in ["(a|b)*abb", "a*|a*a", "ε|ab(c|ε)", "(ab)*a.", ""] {
+            let automata = Automata::from_regex(regex);
+
+            for (method, order) in methods {
+                let options = ToRegexOptions {
+                    method,
+                    order,
+                    ..Default::default()
+                };
+                let word = find_round_trip_word(&automata, &options, &Budget::default());
+                assert_eq!(word, Ok(None), "{regex}");
+            }
+        }
+
+        let budget = Budget::default();
+        let word = find_round_trip_word(&Automata::new_empty(), &Default::default(), &budget);
+        assert_eq!(word, Ok(None));
+
+        let budget = Limits {
+            max_matrix_size: Some(3),
+            ..Default::default()
+        }
+        .start();
+        let automata = Automata::from_regex("(a|b)*abb");
+        let e = find_round_trip_word(&automata, &Default::default(), &budget).unwrap_err();
+        assert!(limits::is_resource_exhausted(&e));
+    }
+
+    #[test]
+    fn other_symbol() {
+        // The alphabet takes every letter and digit, the wildcard still matches more
+        let mut automata = Automata::new(63);
+        for (i, symbol) in ('a'..='z').chain('0'..='9').chain('A'..='Z').enumerate() {
+            automata.transition_matrix[START][i + 1] = Some(symbol);
+            automata.finite_states[i + 1] = true;
+        }
+
+        let dfa = Dfa::from_automata(&automata, &get_alphabet(&[&automata]));
+        assert_eq!(dfa.get_other_symbol(), Some('!'));
+        assert_eq!(
+            find_distinguishing_word(&automata, &Automata::from_regex(".")),
+            Some("!".to_string())
+        );
+    }
+}
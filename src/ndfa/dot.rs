@@ -0,0 +1,131 @@
+use std::fs;
+use std::path::Path;
+
+use super::{Automata, ARBITARY, EPSILON};
+
+pub trait DotLabel {
+    fn to_label(&self) -> String;
+}
+
+impl DotLabel for char {
+    fn to_label(&self) -> String {
+        match *self {
+            ARBITARY => "any".to_string(),
+            symbol => symbol.to_string(),
+        }
+    }
+}
+
+// Regexes on the transitions during the state elimination
+impl DotLabel for String {
+    fn to_label(&self) -> String {
+        if *self == EPSILON {
+            return "ε".to_string();
+        }
+
+        self.clone()
+    }
+}
+
+impl<T: DotLabel + Clone> Automata<T> {
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with_names(&(0..self.size).collect::<Vec<_>>())
+    }
+
+    // States are named by `names`, so they keep their numbers when others are eliminated
+    pub fn to_dot_with_names(&self, names: &[usize]) -> String {
+        let mut dot = String::from("digraph {\n    rankdir=LR;\n    node [shape=circle];\n");
+
+        for (i, name) in names.iter().enumerate() {
+            if self.is_start_state(i) {
+                dot.push_str(&format!(
+                    "    start{name} [shape=point];\n    start{name} -> {name};\n"
+                ));
+            }
+
+            if self.is_finite_state(i) {
+                dot.push_str(&format!("    {name} [shape=doublecircle];\n"));
+            } else {
+                dot.push_str(&format!("    {name};\n"));
+            }
+        }
+
+        for (i, row) in self.transition_matrix.iter().enumerate() {
+            for (j, label_opt) in row.iter().enumerate() {
+                if let Some(label) = label_opt {
+                    dot.push_str(&format!(
+                        "    {} -> {} [label=\"{}\"];\n",
+                        names[i],
+                        names[j],
+                        escape(&label.to_label())
+                    ));
+                }
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn write_dot(&self, dir: &Path, name: &str) -> Result<(), String> {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        fs::write(dir.join(format!("{name}.dot")), self.to_dot()).map_err(|e| e.to_string())
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::ndfa::limits::Budget;
+    use crate::ndfa::Automata;
+
+    #[test]
+    fn states() {
+        // 0 -a-> 1 -b-> 2 -b-> 2
+        let dot = Automata::from_regex("ab*").to_dot();
+
+        assert!(dot.starts_with("digraph {\n    rankdir=LR;\n"));
+        assert!(dot.contains("    start0 [shape=point];\n    start0 -> 0;\n"));
+        assert!(!dot.contains("start1"));
+        assert!(dot.contains("    0;\n"));
+        assert!(dot.contains("    1 [shape=doublecircle];\n"));
+        assert!(dot.contains("    2 [shape=doublecircle];\n"));
+        assert!(dot.contains("    0 -> 1 [label=\"a\"];\n"));
+        assert!(dot.contains("    2 -> 2 [label=\"b\"];\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn merged_labels() {
+        // 0 -a-> 1 -b-> 2 -ε-> 4 and 1 -c-> 3 -ε-> 4, the final states merge into 1 -> 4
+        let mut automata = Automata::from_regex("a(b|c)").prepare_for_state_elimination();
+        automata.eliminate(3, &Budget::default()).unwrap();
+        automata.eliminate(2, &Budget::default()).unwrap();
+
+        let dot = automata.to_dot_with_names(&[0, 1, 4]);
+
+        assert!(dot.contains("    start0 -> 0;\n"));
+        assert!(dot.contains("    4 [shape=doublecircle];\n"));
+        assert!(dot.contains("    0 -> 1 [label=\"a\"];\n"));
+        assert!(dot.contains("    1 -> 4 [label=\"(c|b)\"];\n"));
+        assert_eq!(dot.matches(" -> ").count(), 3);
+    }
+
+    #[test]
+    fn write_dot() {
+        let dir = std::env::temp_dir().join(format!("tfl-dot-{}", std::process::id()));
+        let automata = Automata::from_regex("a|.");
+
+        automata.write_dot(&dir, "automata").unwrap();
+        let dot = fs::read_to_string(dir.join("automata.dot")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(dot, automata.to_dot());
+        assert!(dot.contains("[label=\"any\"]"));
+    }
+}
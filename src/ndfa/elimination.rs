@@ -0,0 +1,223 @@
+use super::limits::Budget;
+use super::trace::TransitionStep;
+use super::{Automata, START};
+
+/// Order in which `Automata::to_regex` eliminates states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum EliminationOrder {
+    /// States in index order
+    Index,
+    /// The state with the fewest incoming × outcoming transitions first
+    FewestEdges,
+    /// The state with the least Delgado–Morais weight first
+    Weight,
+    /// Beam search over the orders, the shortest result wins
+    Search,
+}
+
+// Gets the eliminated state, the combined transitions and the automata after every step
+pub(super) type OnStep<'a> = dyn FnMut(usize, Vec<TransitionStep>, &Automata<String>) + 'a;
+
+impl Automata<String> {
+    pub(super) fn eliminate_states_in(
+        mut self,
+        order: EliminationOrder,
+        search_width: usize,
+        budget: &Budget,
+        on_step: &mut OnStep<'_>,
+    ) -> Result<Option<String>, String> {
+        if order == EliminationOrder::Search {
+            for current in self.search(search_width.max(1), budget)? {
+                let transitions = self.eliminate(current, budget)?;
+                on_step(current, transitions, &self);
+            }
+
+            return Ok(self.get_result());
+        }
+
+        loop {
+            budget.check_time()?;
+
+            let candidates = self.get_eliminable_states();
+
+            let current = match order {
+                EliminationOrder::Index => candidates.first(),
+                EliminationOrder::FewestEdges => {
+                    candidates.iter().min_by_key(|&&i| self.get_edges_count(i))
+                }
+                EliminationOrder::Weight => candidates.iter().min_by_key(|&&i| self.get_weight(i)),
+                EliminationOrder::Search => unreachable!(),
+            };
+
+            match current {
+                Some(&current) => {
+                    let transitions = self.eliminate(current, budget)?;
+                    on_step(current, transitions, &self);
+                }
+                None => break Ok(self.get_result()),
+            }
+        }
+    }
+
+    // Every step keeps `width` automata with the shortest regexes on their transitions,
+    // the states eliminated for the shortest result are returned
+    fn search(&self, width: usize, budget: &Budget) -> Result<Vec<usize>, String> {
+        let mut beam = vec![(self.clone(), Vec::<usize>::new())];
+
+        while !beam[0].0.get_eliminable_states().is_empty() {
+            budget.check_time()?;
+
+            let mut next_beam = Vec::new();
+
+            for (automata, eliminated) in &beam {
+                for current in automata.get_eliminable_states() {
+                    let mut next = automata.clone();
+                    next.eliminate(current, budget)?;
+
+                    let mut next_eliminated = eliminated.clone();
+                    next_eliminated.push(current);
+
+                    next_beam.push((next, next_eliminated));
+                }
+            }
+
+            next_beam.sort_by_key(|(automata, _)| automata.get_total_length());
+            next_beam.truncate(width);
+            beam = next_beam;
+        }
+
+        Ok(beam
+            .into_iter()
+            .min_by_key(|(automata, _)| automata.get_result_length())
+            .unwrap()
+            .1)
+    }
+
+    fn get_eliminable_states(&self) -> Vec<usize> {
+        (0..self.size)
+            .filter(|&i| !self.is_start_state(i) && !self.is_finite_state(i))
+            .collect()
+    }
+
+    // The start and the single final state are left
+    fn get_result(&self) -> Option<String> {
+        let finite = (0..self.size).find(|&i| self.is_finite_state(i)).unwrap();
+
+        self.transition_matrix[START][finite].clone()
+    }
+
+    // An automata without a result is ranked after every one with a result
+    fn get_result_length(&self) -> usize {
+        self.get_result()
+            .map_or(usize::MAX, |regex| regex.chars().count())
+    }
+
+    fn get_edges_count(&self, i: usize) -> usize {
+        self.get_incoming_states(i).len() * self.get_outcoming_states(i).len()
+    }
+
+    // Delgado, Morais. Approximation to the Smallest Regular Expression for a Given Regular Language
+    fn get_weight(&self, i: usize) -> usize {
+        let incoming = self.get_incoming_states(i);
+        let outcoming = self.get_outcoming_states(i);
+
+        let incoming_weight: usize = incoming
+            .iter()
+            .map(|&j| self.get_length(j, i) * outcoming.len().saturating_sub(1))
+            .sum();
+        let outcoming_weight: usize = outcoming
+            .iter()
+            .map(|&j| self.get_length(i, j) * incoming.len().saturating_sub(1))
+            .sum();
+        let cyclic_weight =
+            self.get_length(i, i) * (incoming.len() * outcoming.len()).saturating_sub(1);
+
+        incoming_weight + outcoming_weight + cyclic_weight
+    }
+
+    fn get_length(&self, i: usize, j: usize) -> usize {
+        self.transition_matrix[i][j]
+            .as_ref()
+            .map_or(0, |regex| regex.chars().count())
+    }
+
+    fn get_total_length(&self) -> usize {
+        (0..self.size)
+            .flat_map(|i| (0..self.size).map(move |j| (i, j)))
+            .map(|(i, j)| self.get_length(i, j))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EliminationOrder;
+    use crate::ndfa::dfa::are_equivalent;
+    use crate::ndfa::limits::Budget;
+    use crate::ndfa::{Automata, ToRegexOptions};
+
+    const REGEXES: [&str; 4] = ["(a|b)*abb", "a(b|c)*d|e", "(ab|ba)*a", "ab*(c|ab)*"];
+
+    fn eliminate(automata: &Automata, order: EliminationOrder, width: usize) -> Option<String> {
+        automata
+            .prepare_for_state_elimination()
+            .eliminate_states_in(order, width, &Budget::default(), &mut |_, _, _| {})
+            .unwrap()
+    }
+
+    #[test]
+    fn orders() {
+        let orders = [
+            EliminationOrder::Index,
+            EliminationOrder::FewestEdges,
+            EliminationOrder::Weight,
+            EliminationOrder::Search,
+        ];
+
+        for regex in REGEXES {
+            let automata = Automata::from_regex(regex);
+
+            for order in orders {
+                let options = ToRegexOptions {
+                    order,
+                    simplify: false,
+                    ..Default::default()
+                };
+                let printed =
+                    Automata::from_printed_regex(&automata.to_regex_with(&options).unwrap());
+
+                assert!(are_equivalent(&automata, &printed), "{regex} {order:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn search() {
+        // Wide enough to try every order of the at most 6 eliminated states
+        let width = 720;
+
+        for regex in REGEXES {
+            let automata = Automata::from_regex(regex);
+            let searched = eliminate(&automata, EliminationOrder::Search, width).unwrap();
+
+            for order in [
+                EliminationOrder::Index,
+                EliminationOrder::FewestEdges,
+                EliminationOrder::Weight,
+            ] {
+                let regex_of_order = eliminate(&automata, order, width).unwrap();
+                assert!(
+                    searched.chars().count() <= regex_of_order.chars().count(),
+                    "{regex}: {searched} is longer than {regex_of_order} of {order:?}"
+                );
+            }
+        }
+
+        let empty = Automata::new_empty().prepare_for_state_elimination();
+        assert_eq!(empty.get_result_length(), usize::MAX);
+        assert_eq!(
+            eliminate(&Automata::new_empty(), EliminationOrder::Search, 4),
+            None
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use super::ast::{Atomic, Basic, Concat, LinearizedSymbol, Tree, Union};
+
+/// Marked regex and the sets the Glushkov automata is built from.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Glushkov {
+    /// Every symbol is followed by its index, as in (a1|b2)*a3
+    pub marked_regex: String,
+    pub first: Vec<String>,
+    pub last: Vec<String>,
+    pub follow: Vec<(String, String)>,
+    pub does_epsilon_satisfy: bool,
+}
+
+#[cfg(feature = "serde")]
+impl super::serialization::Serialization for Glushkov {}
+
+impl Tree {
+    pub fn get_glushkov(&self) -> Glushkov {
+        let mut first = self.get_first_set();
+        first.sort_by_key(|symbol| symbol.index);
+
+        let mut last = self.get_last_set();
+        last.sort_by_key(|symbol| symbol.index);
+
+        let mut follow = self.get_follow_set();
+        follow.sort_by_key(|(s1, s2)| (s1.index, s2.index));
+        follow.dedup_by_key(|(s1, s2)| (s1.index, s2.index));
+
+        Glushkov {
+            marked_regex: mark_union(&self.root, true),
+            first: first.iter().map(mark).collect(),
+            last: last.iter().map(mark).collect(),
+            follow: follow.iter().map(|(s1, s2)| (mark(s1), mark(s2))).collect(),
+            does_epsilon_satisfy: self.does_epsilon_satisfy(),
+        }
+    }
+}
+
+impl Glushkov {
+    pub fn to_text(&self) -> String {
+        let follow: Vec<String> = self
+            .follow
+            .iter()
+            .map(|(s1, s2)| format!("{s1} → {s2}"))
+            .collect();
+
+        format!(
+            "marked: {}\nfirst: {{{}}}\nlast: {{{}}}\nfollow: {{{}}}\nε ∈ L: {}\n",
+            self.marked_regex,
+            self.first.join(", "),
+            self.last.join(", "),
+            follow.join(", "),
+            self.does_epsilon_satisfy
+        )
+    }
+}
+
+fn mark(symbol: &LinearizedSymbol) -> String {
+    format!("{}{}", symbol.symbol, symbol.index)
+}
+
+// Course notation with marked symbols, the root union is not wrapped
+fn mark_union(union: &Union, is_root: bool) -> String {
+    let concats: Vec<String> = union.concats.iter().map(mark_concat).collect();
+
+    if is_root || concats.len() == 1 {
+        concats.join("|")
+    } else {
+        format!("({})", concats.join("|"))
+    }
+}
+
+fn mark_concat(concat: &Concat) -> String {
+    if concat.basics.is_empty() {
+        return "ε".to_string();
+    }
+
+    concat.basics.iter().map(mark_basic).collect()
+}
+
+fn mark_basic(basic: &Basic) -> String {
+    let atomic = match &basic.atomic {
+        Atomic::LinearizedSymbol(symbol) => mark(symbol),
+        Atomic::Epsilon => "ε".to_string(),
+        Atomic::Union(union) if basic.is_iter && union.concats.len() == 1 => {
+            format!("({})", mark_union(union, false))
+        }
+        Atomic::Union(union) => mark_union(union, false),
+    };
+
+    if basic.is_iter {
+        format!("{atomic}*")
+    } else {
+        atomic
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ndfa::ast::Tree;
+
+    #[test]
+    fn marked() {
+        let glushkov = Tree::from_regex("(a|b)*a").get_glushkov();
+
+        assert_eq!(glushkov.marked_regex, "(a1|b2)*a3");
+        assert_eq!(glushkov.first, vec!["a1", "b2", "a3"]);
+        assert_eq!(glushkov.last, vec!["a3"]);
+        assert_eq!(glushkov.follow.len(), 6);
+        assert!(!glushkov.does_epsilon_satisfy);
+    }
+
+    #[test]
+    fn groups() {
+        assert_eq!(
+            Tree::from_regex("a(bc)*|ε").get_glushkov().marked_regex,
+            "a1(b2c3)*|ε"
+        );
+        assert_eq!(
+            Tree::from_regex("(ab)c").get_glushkov().marked_regex,
+            "a1b2c3"
+        );
+        assert!(Tree::from_regex("a*").get_glushkov().does_epsilon_satisfy);
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::nfa::Nfa;
+use super::Automata;
+
+/*
+ * JFLAP finite automaton, the states may have any ids:
+ *
+ * <structure>
+ *     <type>fa</type>
+ *     <automaton>
+ *         <state id="0" name="q0"><x>0</x><y>0</y><initial/></state>
+ *         <state id="1" name="q1"><x>100</x><y>0</y><final/></state>
+ *         <transition><from>0</from><to>1</to><read>a</read></transition>
+ *     </automaton>
+ * </structure>
+ *
+ * An empty <read/> is a λ-transition. The '.' wildcard is written as is, JFLAP
+ * reads it as a plain symbol.
+ */
+
+const ERR_NOT_FA: &str = "jff file does not contain a finite automaton";
+const ERR_NO_INITIAL_STATE: &str = "jff automaton has no initial state";
+const ERR_MANY_INITIAL_STATES: &str = "jff automaton has more than one initial state";
+const ERR_UNKNOWN_STATE: &str = "jff transition refers to an unknown state";
+const ERR_NO_STATE_ID: &str = "jff state has no id";
+const ERR_DUPLICATE_STATE: &str = "jff automaton has more than one state with the same id";
+
+// Distance between the states in the layout for JFLAP
+const STEP: usize = 120;
+
+impl Automata {
+    pub fn from_jff(jff: &str) -> Result<Self, String> {
+        let document = roxmltree::Document::parse(jff).map_err(|e| e.to_string())?;
+        let root = document.root_element();
+
+        if get_child_text(root, "type") != Some("fa") {
+            return Err(ERR_NOT_FA.to_string());
+        }
+
+        // Older JFLAP versions put the states right into <structure>
+        let automaton = get_child(root, "automaton").unwrap_or(root);
+
+        let mut indices = HashMap::<&str, usize>::new();
+        let mut initial_state = None;
+        let mut finite_states = Vec::new();
+
+        for state in automaton.children().filter(|n| n.has_tag_name("state")) {
+            let id = state.attribute("id").ok_or(ERR_NO_STATE_ID)?;
+            let index = indices.len();
+            if indices.insert(id.trim(), index).is_some() {
+                return Err(ERR_DUPLICATE_STATE.to_string());
+            }
+
+            if get_child(state, "initial").is_some() {
+                if initial_state.is_some() {
+                    return Err(ERR_MANY_INITIAL_STATES.to_string());
+                }
+                initial_state = Some(index);
+            }
+
+            if get_child(state, "final").is_some() {
+                finite_states.push(index);
+            }
+        }
+
+        let initial_state = initial_state.ok_or(ERR_NO_INITIAL_STATE)?;
+        let mut nfa = Nfa::new(indices.len(), initial_state);
+
+        for state in finite_states {
+            nfa.set_finite(state);
+        }
+
+        for transition in automaton
+            .children()
+            .filter(|n| n.has_tag_name("transition"))
+        {
+            let get_state = |tag| {
+                get_child_text(transition, tag)
+                    .and_then(|id| indices.get(id.trim()))
+                    .copied()
+                    .ok_or(ERR_UNKNOWN_STATE)
+            };
+
+            let from = get_state("from")?;
+            let to = get_state("to")?;
+            let word = get_child_text(transition, "read").unwrap_or_default();
+
+            nfa.add_transition(from, to, word);
+        }
+
+        Ok(nfa.to_automata())
+    }
+
+    pub fn read_jff(path: &Path) -> Result<Self, String> {
+        Self::from_jff(&fs::read_to_string(path).map_err(|e| e.to_string())?)
+    }
+
+    pub fn to_jff(&self) -> String {
+        let mut jff = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n\
+             <structure>\n\
+             \t<type>fa</type>\n\
+             \t<automaton>\n",
+        );
+
+        // States are laid out on a grid, JFLAP stacks them otherwise
+        let columns = (self.size as f64).sqrt().ceil() as usize;
+
+        for i in 0..self.size {
+            jff.push_str(&format!(
+                "\t\t<state id=\"{i}\" name=\"q{i}\">\n\t\t\t<x>{}</x>\n\t\t\t<y>{}</y>\n",
+                (i % columns + 1) * STEP,
+                (i / columns + 1) * STEP
+            ));
+
+            if self.is_start_state(i) {
+                jff.push_str("\t\t\t<initial/>\n");
+            }
+
+            if self.is_finite_state(i) {
+                jff.push_str("\t\t\t<final/>\n");
+            }
+
+            jff.push_str("\t\t</state>\n");
+        }
+
+        for (i, row) in self.transition_matrix.iter().enumerate() {
+            for (j, symbol_opt) in row.iter().enumerate() {
+                if let Some(symbol) = symbol_opt {
+                    jff.push_str(&format!(
+                        "\t\t<transition>\n\t\t\t<from>{i}</from>\n\t\t\t<to>{j}</to>\n\t\t\t<read>{}</read>\n\t\t</transition>\n",
+                        escape(*symbol)
+                    ));
+                }
+            }
+        }
+
+        jff.push_str("\t</automaton>\n</structure>\n");
+        jff
+    }
+
+    pub fn write_jff(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_jff()).map_err(|e| e.to_string())
+    }
+}
+
+fn get_child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    tag: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(tag))
+}
+
+fn get_child_text<'a>(node: roxmltree::Node<'a, '_>, tag: &str) -> Option<&'a str> {
+    get_child(node, tag).map(|n| n.text().unwrap_or_default())
+}
+
+fn escape(symbol: char) -> String {
+    match symbol {
+        '<' => "&lt;".to_string(),
+        '>' => "&gt;".to_string(),
+        '&' => "&amp;".to_string(),
+        symbol => symbol.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ERR_DUPLICATE_STATE, ERR_NO_STATE_ID};
+    use crate::ndfa::dfa::are_equivalent;
+    use crate::ndfa::Automata;
+
+    #[test]
+    fn round_trip() {
+        for regex in ["a", "(a|b)*abb", "ab*c|ε", "a.b"] {
+            let automata = Automata::from_regex(regex);
+            let loaded = Automata::from_jff(&automata.to_jff()).unwrap();
+
+            assert!(are_equivalent(&automata, &loaded), "{regex}");
+        }
+    }
+
+    #[test]
+    fn escaping() {
+        let mut automata = Automata::from_regex("ab");
+        automata.transition_matrix[0][1] = Some('<');
+        automata.transition_matrix[1][2] = Some('&');
+
+        let loaded = Automata::from_jff(&automata.to_jff()).unwrap();
+
+        assert!(are_equivalent(&automata, &loaded));
+        assert_eq!(loaded.transition_matrix[0][1], Some('<'));
+    }
+
+    #[test]
+    fn lambda_and_words() {
+        // q0 -ab-> q1 -λ-> q2, q2 -c-> q2, with arbitrary ids
+        let jff = "<structure><type>fa</type><automaton>\
+            <state id=\"7\"><initial/></state>\
+            <state id=\"3\"/>\
+            <state id=\"5\"><final/></state>\
+            <transition><from>7</from><to>3</to><read>ab</read></transition>\
+            <transition><from>3</from><to>5</to><read/></transition>\
+            <transition><from>5</from><to>5</to><read>c</read></transition>\
+            </automaton></structure>";
+
+        let loaded = Automata::from_jff(jff).unwrap();
+
+        assert!(are_equivalent(&loaded, &Automata::from_regex("abc*")));
+        assert!(
+            !loaded.is_start_state(1)
+                && loaded.transition_matrix.iter().all(|row| row[0].is_none())
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Automata::from_jff("<structure><type>pda</type></structure>").is_err());
+        assert!(Automata::from_jff("<structure><type>fa</type><automaton/></structure>").is_err());
+        assert!(Automata::from_jff("<structure><type>fa").is_err());
+    }
+
+    #[test]
+    fn state_ids() {
+        let jff = |states: &str| {
+            format!(
+                "<structure><type>fa</type><automaton>{states}\
+                 <transition><from>0</from><to>1</to><read>a</read></transition>\
+                 </automaton></structure>"
+            )
+        };
+
+        assert!(Automata::from_jff(&jff(
+            "<state id=\"0\"><initial/></state><state id=\"1\"><final/></state>"
+        ))
+        .is_ok());
+        assert_eq!(
+            Automata::from_jff(&jff(
+                "<state id=\"0\"><initial/></state><state id=\"0\"><final/></state>"
+            ))
+            .err(),
+            Some(ERR_DUPLICATE_STATE.to_string())
+        );
+        assert_eq!(
+            Automata::from_jff(&jff(
+                "<state id=\"0\"><initial/></state><state><final/></state>"
+            ))
+            .err(),
+            Some(ERR_NO_STATE_ID.to_string())
+        );
+    }
+}
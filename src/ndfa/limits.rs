@@ -0,0 +1,137 @@
+use std::time::{Duration, Instant};
+
+/*
+ * Every `try_*` function of the conversion takes a `Budget` and fails with
+ * `ERR_RESOURCE_EXHAUSTED` once it runs out. The function without the prefix
+ * runs it with `unlimited`.
+ */
+
+pub const ERR_RESOURCE_EXHAUSTED: &str = "resource exhausted";
+
+/// Bounds of a conversion, none by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Limits {
+    /// States of the product automata of an intersection
+    pub max_product_states: Option<usize>,
+    /// States of the automatas built for a regex and converted back to one
+    pub max_matrix_size: Option<usize>,
+    /// Wall-clock time of building the automata and converting it back
+    pub timeout: Option<Duration>,
+}
+
+impl Limits {
+    /// The timeout runs from now on.
+    pub fn start(&self) -> Budget {
+        Budget {
+            limits: *self,
+            deadline: self.timeout.map(|timeout| Instant::now() + timeout),
+        }
+    }
+}
+
+/// Limits of a conversion in progress, unlimited by default.
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    limits: Limits,
+    deadline: Option<Instant>,
+}
+
+impl Budget {
+    pub fn check_time(&self) -> Result<(), String> {
+        match self.deadline {
+            Some(deadline) if Instant::now() > deadline => Err(format!(
+                "{ERR_RESOURCE_EXHAUSTED}: no result in {:?}",
+                self.limits.timeout.unwrap_or_default()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_product_states(&self, states: usize) -> Result<(), String> {
+        check_size("product states", states, self.limits.max_product_states)
+    }
+
+    pub fn check_matrix_size(&self, size: usize) -> Result<(), String> {
+        check_size("states", size, self.limits.max_matrix_size)
+    }
+}
+
+/// Runs a `try_*` function without limits, so it can not fail.
+pub fn unlimited<T>(f: impl FnOnce(&Budget) -> Result<T, String>) -> T {
+    f(&Budget::default()).expect("an unlimited budget can not run out")
+}
+
+/// Whether the error is of a limit rather than of the regex.
+pub fn is_resource_exhausted(e: &str) -> bool {
+    e.starts_with(ERR_RESOURCE_EXHAUSTED)
+}
+
+fn check_size(name: &str, size: usize, max_size: Option<usize>) -> Result<(), String> {
+    match max_size {
+        Some(max_size) if size > max_size => Err(format!(
+            "{ERR_RESOURCE_EXHAUSTED}: more than {max_size} {name}"
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{is_resource_exhausted, Budget, Limits};
+    use crate::ndfa::ast::Tree;
+    use crate::ndfa::{self, simplify, Automata};
+
+    #[test]
+    fn limits() {
+        let budget = Limits {
+            max_product_states: Some(5),
+            ..Default::default()
+        }
+        .start();
+
+        assert!(budget.check_product_states(5).is_ok());
+        assert!(is_resource_exhausted(
+            &budget.check_product_states(6).unwrap_err()
+        ));
+        assert!(budget.check_matrix_size(usize::MAX).is_ok());
+
+        let sized = Limits {
+            max_matrix_size: Some(3),
+            ..Default::default()
+        }
+        .start();
+        assert!(Automata::try_from_regex("ab", &sized).is_ok());
+        let e = Automata::try_from_regex("abc", &sized).unwrap_err();
+        assert!(is_resource_exhausted(&e));
+
+        let (a1, a2) = (
+            Automata::from_regex("(ab)*a.*"),
+            Automata::from_regex("(ab)*a"),
+        );
+        let e = ndfa::try_intersection_with_stats(&a1, &a2, &budget).unwrap_err();
+        assert!(is_resource_exhausted(&e));
+        assert!(ndfa::try_intersection_with_stats(&a1, &a2, &Budget::default()).is_ok());
+
+        let budget = Limits {
+            timeout: Some(Duration::ZERO),
+            ..Default::default()
+        }
+        .start();
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(is_resource_exhausted(&budget.check_time().unwrap_err()));
+        assert!(Budget::default().check_time().is_ok());
+
+        // Every stage of the conversion back gives up
+        let automata = Automata::from_regex("(a|b)*abb");
+        let e = automata
+            .try_to_regex_with(&Default::default(), &budget)
+            .unwrap_err();
+        assert!(is_resource_exhausted(&e));
+        let e = Tree::try_from_regex("(a|b)*", &budget).unwrap_err();
+        assert!(is_resource_exhausted(&e));
+        let e = simplify::try_simplify(&Tree::from_regex("a|a"), &budget).unwrap_err();
+        assert!(is_resource_exhausted(&e));
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use super::{Automata, START};
+
+/// Arbitrary NFA with ε-transitions and multi-symbol labels, as drawn by hand or
+/// loaded from other tools. `Automata` keeps a single symbol on every transition
+/// and no transitions to the start state, so the NFA has to be converted.
+#[derive(Debug, Clone, Default)]
+pub struct Nfa {
+    size: usize,
+    initial_state: usize,
+    finite_states: Vec<bool>,
+    // None stands for ε
+    transitions: Vec<(usize, usize, Option<char>)>,
+}
+
+impl Nfa {
+    pub fn new(size: usize, initial_state: usize) -> Self {
+        Self {
+            size,
+            initial_state,
+            finite_states: vec![false; size],
+            transitions: Vec::new(),
+        }
+    }
+
+    pub fn set_finite(&mut self, state: usize) {
+        self.finite_states[state] = true;
+    }
+
+    /// Adds a path reading `word` through new intermediate states, an empty word is ε.
+    pub fn add_transition(&mut self, from: usize, to: usize, word: &str) {
+        let symbols: Vec<char> = word.chars().collect();
+
+        let Some((last, prefix)) = symbols.split_last() else {
+            self.transitions.push((from, to, None));
+            return;
+        };
+
+        let mut current = from;
+        for &symbol in prefix {
+            let next = self.add_state();
+            self.transitions.push((current, next, Some(symbol)));
+            current = next;
+        }
+
+        self.transitions.push((current, to, Some(*last)));
+    }
+
+    fn add_state(&mut self) -> usize {
+        self.finite_states.push(false);
+        self.size += 1;
+
+        self.size - 1
+    }
+
+    fn get_epsilon_closure(&self, state: usize) -> Vec<usize> {
+        let mut closure = vec![state];
+        let mut states_deq = VecDeque::from([state]);
+
+        while let Some(current) = states_deq.pop_front() {
+            for &(from, to, symbol_opt) in &self.transitions {
+                if from == current && symbol_opt.is_none() && !closure.contains(&to) {
+                    closure.push(to);
+                    states_deq.push_back(to);
+                }
+            }
+        }
+
+        closure
+    }
+
+    // Transitions on symbols from every state of the ε-closure
+    fn remove_epsilon_transitions(&self) -> (Vec<Vec<(char, usize)>>, Vec<bool>) {
+        let mut transitions = vec![Vec::new(); self.size];
+        let mut finite_states = vec![false; self.size];
+
+        for state in 0..self.size {
+            for reachable in self.get_epsilon_closure(state) {
+                finite_states[state] |= self.finite_states[reachable];
+
+                for &(from, to, symbol_opt) in &self.transitions {
+                    let Some(symbol) = symbol_opt else {
+                        continue;
+                    };
+
+                    if from == reachable && !transitions[state].contains(&(symbol, to)) {
+                        transitions[state].push((symbol, to));
+                    }
+                }
+            }
+        }
+
+        (transitions, finite_states)
+    }
+
+    /*
+     * Every state of the automata is a pair (NFA state, symbol it is entered by),
+     * so all transitions to a state share the symbol, like in the Glushkov automata.
+     * Only the pairs reachable from the initial state are built.
+     */
+    pub fn to_automata(&self) -> Automata {
+        let (transitions, finite_states) = self.remove_epsilon_transitions();
+
+        let mut indices = HashMap::<(usize, char), usize>::new();
+        let mut edges = Vec::<(usize, usize, char)>::new();
+        let mut states_deq = VecDeque::<(usize, usize)>::new();
+        states_deq.push_back((START, self.initial_state));
+
+        while let Some((from, state)) = states_deq.pop_front() {
+            for &(symbol, next_state) in &transitions[state] {
+                let to = match indices.get(&(next_state, symbol)) {
+                    Some(&to) => to,
+                    None => {
+                        let to = indices.len() + 1;
+                        indices.insert((next_state, symbol), to);
+                        states_deq.push_back((to, next_state));
+                        to
+                    }
+                };
+
+                edges.push((from, to, symbol));
+            }
+        }
+
+        let mut automata = Automata::new(indices.len() + 1);
+        for (from, to, symbol) in edges {
+            automata.transition_matrix[from][to] = Some(symbol);
+        }
+
+        automata.finite_states[START] = finite_states[self.initial_state];
+        for (&(state, _), &index) in &indices {
+            automata.finite_states[index] = finite_states[state];
+        }
+
+        automata
+    }
+}
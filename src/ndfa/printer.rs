@@ -0,0 +1,218 @@
+use super::ast::{Atomic, Basic, Concat, Tree, Union};
+use super::ARBITARY;
+
+/// Target syntax of a printed regex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Dialect {
+    /// Course notation: `|`, `*`, `ε` and `.`, readable back by `parser` and `ast`
+    Course,
+    /// Syntax of the `regex` crate
+    Rust,
+    /// PCRE, also accepted by `fancy-regex`
+    Pcre,
+    /// POSIX extended regular expressions
+    Posix,
+}
+
+// Binding strength of a printed subexpression, from the tightest one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Precedence {
+    Epsilon,
+    Atom,
+    Postfix,
+    Concat,
+    Union,
+}
+
+impl Dialect {
+    pub fn print(&self, tree: &Tree) -> String {
+        self.print_union(&tree.root).0
+    }
+
+    pub fn print_anchored(&self, tree: &Tree) -> String {
+        let (regex, precedence) = self.print_union(&tree.root);
+
+        match precedence {
+            Precedence::Epsilon => "^$".to_string(),
+            // ^a|b$ means (^a)|(b$) everywhere but in the course notation
+            Precedence::Union if *self != Dialect::Course => format!("^{}$", self.group(&regex)),
+            _ => format!("^{regex}$"),
+        }
+    }
+
+    fn print_union(&self, union: &Union) -> (String, Precedence) {
+        let mut branches = Vec::new();
+        let mut has_epsilon = false;
+
+        for concat in &union.concats {
+            let (regex, precedence) = self.print_concat(concat);
+
+            if precedence == Precedence::Epsilon && *self != Dialect::Course {
+                has_epsilon = true;
+            } else {
+                branches.push((regex, precedence));
+            }
+        }
+
+        // Course notation keeps ε as a branch, others make the union optional
+        let (regex, precedence) = match branches.len() {
+            0 => return (self.epsilon().to_string(), Precedence::Epsilon),
+            1 => branches.pop().unwrap(),
+            _ => {
+                let regexes: Vec<String> = branches.into_iter().map(|(regex, _)| regex).collect();
+                (regexes.join("|"), Precedence::Union)
+            }
+        };
+
+        if !has_epsilon {
+            return (regex, precedence);
+        }
+
+        (
+            format!("{}?", self.wrap_if_needed(&regex, precedence)),
+            Precedence::Postfix,
+        )
+    }
+
+    fn print_concat(&self, concat: &Concat) -> (String, Precedence) {
+        let mut parts = Vec::new();
+
+        for basic in &concat.basics {
+            let (regex, precedence) = self.print_basic(basic);
+
+            if precedence != Precedence::Epsilon {
+                parts.push((regex, precedence));
+            }
+        }
+
+        match parts.len() {
+            0 => (self.epsilon().to_string(), Precedence::Epsilon),
+            1 => parts.pop().unwrap(),
+            _ => {
+                let mut result = String::new();
+                for (regex, precedence) in parts {
+                    if precedence == Precedence::Union {
+                        result.push_str(&self.group(&regex));
+                    } else {
+                        result.push_str(&regex);
+                    }
+                }
+
+                (result, Precedence::Concat)
+            }
+        }
+    }
+
+    fn print_basic(&self, basic: &Basic) -> (String, Precedence) {
+        let (regex, precedence) = self.print_atomic(&basic.atomic);
+
+        if !basic.is_iter || precedence == Precedence::Epsilon {
+            return (regex, precedence);
+        }
+
+        (
+            format!("{}*", self.wrap_if_needed(&regex, precedence)),
+            Precedence::Postfix,
+        )
+    }
+
+    fn print_atomic(&self, atomic: &Atomic) -> (String, Precedence) {
+        match atomic {
+            Atomic::LinearizedSymbol(linearized_symbol) => {
+                (self.escape(linearized_symbol.symbol), Precedence::Atom)
+            }
+            Atomic::Epsilon => (self.epsilon().to_string(), Precedence::Epsilon),
+            Atomic::Union(union) => self.print_union(union),
+        }
+    }
+
+    fn wrap_if_needed(&self, regex: &str, precedence: Precedence) -> String {
+        if precedence == Precedence::Atom {
+            return regex.to_string();
+        }
+
+        self.group(regex)
+    }
+
+    fn group(&self, regex: &str) -> String {
+        match self {
+            Dialect::Course | Dialect::Posix => format!("({regex})"),
+            Dialect::Rust | Dialect::Pcre => format!("(?:{regex})"),
+        }
+    }
+
+    fn epsilon(&self) -> &'static str {
+        match self {
+            Dialect::Course => "ε",
+            _ => "",
+        }
+    }
+
+    fn escape(&self, symbol: char) -> String {
+        if symbol == ARBITARY {
+            return symbol.to_string();
+        }
+
+        match self {
+            Dialect::Course => symbol.to_string(),
+            Dialect::Rust => regex::escape(&symbol.to_string()),
+            Dialect::Pcre => fancy_regex::escape(&symbol.to_string()).to_string(),
+            Dialect::Posix => {
+                if "\\^$.[]|()*+?{}".contains(symbol) {
+                    format!("\\{symbol}")
+                } else {
+                    symbol.to_string()
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dialect;
+    use crate::ndfa::ast::Tree;
+
+    fn print(regex: &str, dialect: Dialect) -> String {
+        dialect.print_anchored(&Tree::from_regex(regex))
+    }
+
+    #[test]
+    fn course() {
+        assert_eq!(print("a(b|ε)", Dialect::Course), "^a(b|ε)$");
+        assert_eq!(print("a|", Dialect::Course), "^a|ε$");
+        assert_eq!(print("((a))*b", Dialect::Course), "^a*b$");
+        assert_eq!(print("ε", Dialect::Course), "^$");
+    }
+
+    #[test]
+    fn epsilon() {
+        assert_eq!(print("a(b|ε)", Dialect::Rust), "^ab?$");
+        assert_eq!(print("(ab|)c", Dialect::Pcre), "^(?:ab)?c$");
+        assert_eq!(print("(a|b|ε)*", Dialect::Posix), "^((a|b)?)*$");
+        assert_eq!(print("aεb", Dialect::Posix), "^ab$");
+        assert_eq!(print("(ε|ε)*", Dialect::Rust), "^$");
+    }
+
+    #[test]
+    fn anchors() {
+        assert_eq!(print("a|b", Dialect::Rust), "^(?:a|b)$");
+        assert_eq!(print("a|b", Dialect::Pcre), "^(?:a|b)$");
+        assert_eq!(print("a|b", Dialect::Posix), "^(a|b)$");
+    }
+
+    #[test]
+    fn escaping() {
+        assert_eq!(print("$a.", Dialect::Rust), "^\\$a.$");
+        assert_eq!(print("$a.", Dialect::Posix), "^\\$a.$");
+    }
+
+    #[test]
+    fn engines() {
+        let rust = regex::Regex::new(&print("(a|)b*(c|ε)", Dialect::Rust)).unwrap();
+        assert!(rust.is_match("bbc") && rust.is_match("a") && !rust.is_match("ca"));
+
+        let pcre = fancy_regex::Regex::new(&print("(a|)b*(c|ε)", Dialect::Pcre)).unwrap();
+        assert!(pcre.is_match("").unwrap() && !pcre.is_match("aa").unwrap());
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::VecDeque;
+
+use super::dot::DotLabel;
+use super::{Automata, START};
+
+/// Diagram languages of `Automata::render`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum RenderFormat {
+    Dot,
+    /// Mermaid `stateDiagram-v2`
+    Mermaid,
+    /// TikZ picture for the `automata` library
+    Tikz,
+}
+
+// Distance between the layers and between the states of a layer in TikZ, cm
+const LAYER_DISTANCE: usize = 3;
+const STATE_DISTANCE: usize = 2;
+
+impl<T: DotLabel + Clone> Automata<T> {
+    pub fn render(&self, format: RenderFormat) -> String {
+        match format {
+            RenderFormat::Dot => self.to_dot(),
+            RenderFormat::Mermaid => self.to_mermaid(),
+            RenderFormat::Tikz => self.to_tikz(),
+        }
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("stateDiagram-v2\n    direction LR\n");
+
+        for i in 0..self.size {
+            if self.is_start_state(i) {
+                mermaid.push_str(&format!("    [*] --> q{i}\n"));
+            }
+
+            if self.is_finite_state(i) {
+                mermaid.push_str(&format!("    q{i} --> [*]\n"));
+            }
+        }
+
+        for (i, j, label) in self.get_transitions() {
+            mermaid.push_str(&format!("    q{i} --> q{j} : {}\n", escape_mermaid(&label)));
+        }
+
+        mermaid
+    }
+
+    pub fn to_tikz(&self) -> String {
+        let layers = self.get_layers();
+
+        let mut tikz = String::from(
+            "\\begin{tikzpicture}[->, >=stealth, auto, node distance=2cm, semithick]\n",
+        );
+
+        for (i, &layer) in layers.iter().enumerate() {
+            let position = layers[..i].iter().filter(|&&l| l == layer).count();
+
+            let mut options = vec!["state"];
+            if self.is_start_state(i) {
+                options.push("initial");
+            }
+            if self.is_finite_state(i) {
+                options.push("accepting");
+            }
+
+            tikz.push_str(&format!(
+                "    \\node[{}] (q{i}) at ({}, {}) {{$q_{{{i}}}$}};\n",
+                options.join(", "),
+                layer * LAYER_DISTANCE,
+                -((position * STATE_DISTANCE) as isize)
+            ));
+        }
+
+        for (i, j, label) in self.get_transitions() {
+            // Backward edges are bent, so they do not overlap the forward ones
+            let bend = if i == j {
+                " [loop above]"
+            } else if layers[j] <= layers[i] {
+                " [bend left]"
+            } else {
+                ""
+            };
+
+            tikz.push_str(&format!(
+                "    \\path (q{i}) edge{bend} node {{{}}} (q{j});\n",
+                escape_tikz(&label)
+            ));
+        }
+
+        tikz.push_str("\\end{tikzpicture}\n");
+        tikz
+    }
+
+    fn get_transitions(&self) -> Vec<(usize, usize, String)> {
+        let mut transitions = Vec::new();
+
+        for (i, row) in self.transition_matrix.iter().enumerate() {
+            for (j, label_opt) in row.iter().enumerate() {
+                if let Some(label) = label_opt {
+                    transitions.push((i, j, label.to_label()));
+                }
+            }
+        }
+
+        transitions
+    }
+
+    // Distance from the start state, the unreachable states go after the farthest ones
+    fn get_layers(&self) -> Vec<usize> {
+        let mut layers = vec![None; self.size];
+        layers[START] = Some(0);
+
+        let mut states_deq = VecDeque::from([START]);
+        while let Some(i) = states_deq.pop_front() {
+            for j in 0..self.size {
+                if self.transition_matrix[i][j].is_some() && layers[j].is_none() {
+                    layers[j] = Some(layers[i].unwrap() + 1);
+                    states_deq.push_back(j);
+                }
+            }
+        }
+
+        let unreachable_layer = layers.iter().flatten().max().unwrap() + 1;
+
+        layers
+            .into_iter()
+            .map(|layer| layer.unwrap_or(unreachable_layer))
+            .collect()
+    }
+}
+
+// Mermaid breaks on some symbols in labels, but reads entity codes
+fn escape_mermaid(label: &str) -> String {
+    label
+        .chars()
+        .map(|symbol| match symbol {
+            ':' | ';' | '#' | '"' | '<' | '>' | '{' | '}' | '|' => format!("#{};", symbol as u32),
+            symbol => symbol.to_string(),
+        })
+        .collect()
+}
+
+fn escape_tikz(label: &str) -> String {
+    label
+        .chars()
+        .map(|symbol| match symbol {
+            'ε' => "$\\varepsilon$".to_string(),
+            '\\' => "\\textbackslash{}".to_string(),
+            '^' => "\\textasciicircum{}".to_string(),
+            '~' => "\\textasciitilde{}".to_string(),
+            '|' => "\\textbar{}".to_string(),
+            '*' => "$^*$".to_string(),
+            '#' | '$' | '%' | '&' | '_' | '{' | '}' => format!("\\{symbol}"),
+            symbol => symbol.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ndfa::Automata;
+
+    #[test]
+    fn layers() {
+        // 0 -a-> 1 -b-> 2 -b-> 3 -b-> 3
+        let automata = Automata::from_regex("abb*");
+
+        assert_eq!(automata.get_layers(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn mermaid() {
+        let mermaid = Automata::from_regex("a|.").to_mermaid();
+
+        assert!(mermaid.contains("[*] --> q0"));
+        assert!(mermaid.contains("q1 --> [*]"));
+        assert!(mermaid.contains("q0 --> q2 : any"));
+    }
+
+    #[test]
+    fn tikz() {
+        let tikz = Automata::from_regex("ab*").to_tikz();
+
+        assert!(tikz.contains("\\node[state, initial] (q0) at (0, 0)"));
+        assert!(tikz.contains("\\node[state, accepting] (q2) at (6, 0)"));
+        assert!(tikz.contains("\\path (q2) edge [loop above] node {b} (q2);"));
+    }
+}
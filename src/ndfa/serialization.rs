@@ -0,0 +1,109 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::ast::Tree;
+use super::{Automata, START};
+
+const ERR_INCONSISTENT_AUTOMATA: &str = "sizes of the automata fields do not match";
+const ERR_TRANSITION_TO_START: &str = "automata has a transition to its start state";
+
+/// JSON for reading and MessagePack for compact storage.
+pub trait Serialization: Serialize + DeserializeOwned {
+    fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| e.to_string())
+    }
+
+    fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json)
+            .map_err(|e| e.to_string())
+            .and_then(Self::validate)
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        rmp_serde::to_vec(self).map_err(|e| e.to_string())
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        rmp_serde::from_slice(bytes)
+            .map_err(|e| e.to_string())
+            .and_then(Self::validate)
+    }
+
+    // Deserialized values may have been edited by hand
+    fn validate(self) -> Result<Self, String> {
+        Ok(self)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + Clone> Serialization for Automata<T> {
+    fn validate(self) -> Result<Self, String> {
+        let is_consistent = self.size > 0
+            && self.start_states.len() == self.size
+            && self.finite_states.len() == self.size
+            && self.transition_matrix.len() == self.size
+            && self
+                .transition_matrix
+                .iter()
+                .all(|row| row.len() == self.size);
+
+        if !is_consistent {
+            return Err(ERR_INCONSISTENT_AUTOMATA.to_string());
+        }
+
+        // The union and the concatenation reuse the start state as is
+        if self
+            .transition_matrix
+            .iter()
+            .any(|row| row[START].is_some())
+        {
+            return Err(ERR_TRANSITION_TO_START.to_string());
+        }
+
+        Ok(self)
+    }
+}
+
+impl Serialization for Tree {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Serialization, ERR_TRANSITION_TO_START};
+    use crate::ndfa::ast::Tree;
+    use crate::ndfa::dfa::are_equivalent;
+    use crate::ndfa::printer::Dialect;
+    use crate::ndfa::Automata;
+
+    #[test]
+    fn automata() {
+        let automata = Automata::from_regex("(a|b)*abb|ε");
+
+        let from_json = Automata::from_json(&automata.to_json().unwrap()).unwrap();
+        let from_bytes = Automata::from_bytes(&automata.to_bytes().unwrap()).unwrap();
+
+        assert!(are_equivalent(&automata, &from_json));
+        assert!(are_equivalent(&automata, &from_bytes));
+        assert_eq!(from_bytes.is_finite_state(0), automata.is_finite_state(0));
+    }
+
+    #[test]
+    fn tree() {
+        let tree = Tree::from_regex("a(b|ε)*c");
+        let from_bytes = Tree::from_bytes(&tree.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(Dialect::Course.print(&from_bytes), "a(b|ε)*c");
+        assert_eq!(from_bytes.linearized_symbols, tree.linearized_symbols);
+    }
+
+    #[test]
+    fn inconsistent() {
+        let json = r#"{"size":2,"transition_matrix":[[null]],"start_states":[true],"finite_states":[false]}"#;
+
+        assert!(Automata::<char>::from_json(json).is_err());
+
+        let json = r#"{"size":2,"transition_matrix":[[null,"a"],["b",null]],"start_states":[true,false],"finite_states":[false,true]}"#;
+        assert_eq!(
+            Automata::<char>::from_json(json).err(),
+            Some(ERR_TRANSITION_TO_START.to_string())
+        );
+    }
+}
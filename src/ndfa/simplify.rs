@@ -0,0 +1,483 @@
+use super::ast::{Atomic, Basic, Concat, Tree, Union};
+use super::limits::{self, Budget};
+use super::printer::Dialect;
+
+// Every pass shrinks the tree, the limit only guards against rule cycles
+const MAX_PASSES: usize = 64;
+
+/*
+ * Rewrite rules, applied bottom-up until nothing changes:
+ *
+ * (r) -> r, ((r*))* -> r*, ε* -> ε                     (basic)
+ * (ε|r)* -> r*, (r|s*)* -> (r|s)*                        (basic)
+ * r ε -> r, r*r* -> r*                                    (concat)
+ * (ε|r)r* -> r*, r*(ε|r) -> r*                            (concat)
+ * r|r -> r, r|r* -> r*, ε|r -> r if ε ∈ r                 (union)
+ * ε|r r* -> r*, ε|r* r -> r*                              (union)
+ * r s|r t -> r(s|t), s r|t r -> (s|t)r                    (union)
+ *
+ * Subexpressions are compared by structure, the positions of their symbols
+ * are stale while rewriting.
+ */
+
+pub fn simplify(tree: &Tree) -> Tree {
+    limits::unlimited(|budget| try_simplify(tree, budget))
+}
+
+/// The budget is checked on every pass and for every union.
+pub fn try_simplify(tree: &Tree, budget: &Budget) -> Result<Tree, String> {
+    let mut root = tree.root.clone();
+
+    for _ in 0..MAX_PASSES {
+        // Before the tree is cloned, which takes long for the large ones
+        budget.check_time()?;
+        let simplified = simplify_union(root.clone(), budget)?;
+
+        if same_union(&simplified, &root) {
+            break;
+        }
+
+        root = simplified;
+    }
+
+    // Positions are stale after rewriting, so the tree is linearized anew
+    let regex = Dialect::Course.print(&Tree {
+        root,
+        linearized_symbols: tree.linearized_symbols,
+    });
+
+    Tree::try_from_regex(&regex, budget)
+}
+
+impl Tree {
+    /// Number of symbols, ε-atoms and operators of the regex.
+    pub fn size(&self) -> usize {
+        size_of_union(&self.root)
+    }
+}
+
+fn size_of_union(union: &Union) -> usize {
+    let operators = union.concats.len().saturating_sub(1);
+
+    union.concats.iter().map(size_of_concat).sum::<usize>() + operators
+}
+
+fn size_of_concat(concat: &Concat) -> usize {
+    // An empty concat is an implicit ε
+    if concat.basics.is_empty() {
+        return 1;
+    }
+
+    let operators = concat.basics.len().saturating_sub(1);
+
+    concat.basics.iter().map(size_of_basic).sum::<usize>() + operators
+}
+
+fn size_of_basic(basic: &Basic) -> usize {
+    let operand = match &basic.atomic {
+        Atomic::LinearizedSymbol(_) | Atomic::Epsilon => 1,
+        Atomic::Union(union) => size_of_union(union),
+    };
+
+    operand + usize::from(basic.is_iter)
+}
+
+// Union
+
+fn simplify_union(union: Union, budget: &Budget) -> Result<Union, String> {
+    budget.check_time()?;
+
+    let mut concats = Vec::<Concat>::new();
+
+    for concat in union.concats {
+        let concat = simplify_concat(concat, budget)?;
+        // (r|s)|t -> r|s|t
+        match as_union(&concat) {
+            Some(inner) => concats.extend(inner.concats.iter().cloned()),
+            None => concats.push(concat),
+        }
+    }
+
+    // r|r -> r
+    let mut unique = Vec::<Concat>::new();
+    for concat in concats {
+        if !unique.iter().any(|u| same_concat(u, &concat)) {
+            unique.push(concat);
+        }
+    }
+
+    // r|r* -> r*
+    let starred: Vec<Basic> = unique
+        .iter()
+        .filter_map(|concat| match concat.basics.as_slice() {
+            [basic] if basic.is_iter => Some(basic.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut concats: Vec<Concat> = unique
+        .into_iter()
+        .filter(|concat| {
+            !starred
+                .iter()
+                .any(|basic| starred_operand_equals(&basic.atomic, &concat.basics))
+        })
+        .collect();
+
+    if let Some(i) = concats.iter().position(|c| c.basics.is_empty()) {
+        let has_other_epsilon = concats
+            .iter()
+            .enumerate()
+            .any(|(j, c)| j != i && does_epsilon_satisfy_concat(c));
+
+        if let Some((j, starred)) = find_unfolded_star(&concats) {
+            // ε|r r* -> r*
+            concats[j] = Concat::new(vec![starred]);
+            concats.remove(i);
+        } else if has_other_epsilon {
+            // ε|r -> r if ε ∈ r
+            concats.remove(i);
+        }
+    }
+
+    let concats = factor_prefixes(concats, budget)?;
+    let concats = factor_suffixes(concats, budget)?;
+
+    Ok(Union::new(concats))
+}
+
+// The concat is a single non-iterated group
+fn as_union(concat: &Concat) -> Option<&Union> {
+    match concat.basics.as_slice() {
+        [Basic {
+            atomic: Atomic::Union(union),
+            is_iter: false,
+        }] => Some(union),
+        _ => None,
+    }
+}
+
+fn find_unfolded_star(concats: &[Concat]) -> Option<(usize, Basic)> {
+    for (i, concat) in concats.iter().enumerate() {
+        let basics = &concat.basics;
+        if basics.len() < 2 {
+            continue;
+        }
+
+        let (last, prefix) = basics.split_last().unwrap();
+        if last.is_iter && starred_operand_equals(&last.atomic, prefix) {
+            return Some((i, last.clone()));
+        }
+
+        let (first, suffix) = basics.split_first().unwrap();
+        if first.is_iter && starred_operand_equals(&first.atomic, suffix) {
+            return Some((i, first.clone()));
+        }
+    }
+
+    None
+}
+
+fn starred_operand_equals(atomic: &Atomic, basics: &[Basic]) -> bool {
+    match atomic {
+        Atomic::Union(union) if union.concats.len() == 1 => {
+            same_basics(&union.concats[0].basics, basics)
+        }
+        _ => basics.len() == 1 && !basics[0].is_iter && same_atomic(&basics[0].atomic, atomic),
+    }
+}
+
+fn factor_prefixes(concats: Vec<Concat>, budget: &Budget) -> Result<Vec<Concat>, String> {
+    factor(
+        concats,
+        budget,
+        |basics| basics.first().cloned(),
+        |basics| basics[1..].to_vec(),
+        true,
+    )
+}
+
+fn factor_suffixes(concats: Vec<Concat>, budget: &Budget) -> Result<Vec<Concat>, String> {
+    factor(
+        concats,
+        budget,
+        |basics| basics.last().cloned(),
+        |basics| basics[..basics.len() - 1].to_vec(),
+        false,
+    )
+}
+
+// Groups branches sharing the first (last) basic: r s|r t -> r(s|t)
+fn factor(
+    concats: Vec<Concat>,
+    budget: &Budget,
+    get_common: impl Fn(&[Basic]) -> Option<Basic>,
+    get_rest: impl Fn(&[Basic]) -> Vec<Basic>,
+    is_prefix: bool,
+) -> Result<Vec<Concat>, String> {
+    let mut groups = Vec::<(Option<Basic>, Vec<Concat>)>::new();
+
+    for concat in concats {
+        let common = get_common(&concat.basics);
+
+        match groups.iter_mut().find(|(c, _)| match (c, &common) {
+            (Some(c), Some(common)) => same_basic(c, common),
+            _ => false,
+        }) {
+            Some((_, members)) => members.push(concat),
+            None => groups.push((common, vec![concat])),
+        }
+    }
+
+    let mut result = Vec::new();
+    for (common, mut members) in groups {
+        if members.len() == 1 {
+            result.push(members.pop().unwrap());
+            continue;
+        }
+
+        let rests = members
+            .iter()
+            .map(|c| Concat::new(get_rest(&c.basics)))
+            .collect();
+        let rest = Basic::new(Atomic::Union(Union::new(rests)), false);
+
+        let basics = if is_prefix {
+            vec![common.unwrap(), rest]
+        } else {
+            vec![rest, common.unwrap()]
+        };
+
+        result.push(simplify_concat(Concat::new(basics), budget)?);
+    }
+
+    Ok(result)
+}
+
+// Concat
+
+fn simplify_concat(concat: Concat, budget: &Budget) -> Result<Concat, String> {
+    let mut basics = Vec::<Basic>::new();
+
+    for basic in concat.basics {
+        match simplify_basic(basic, budget)? {
+            // r ε -> r
+            Basic {
+                atomic: Atomic::Epsilon,
+                ..
+            } => {}
+            // r(st) -> rst
+            Basic {
+                atomic: Atomic::Union(union),
+                is_iter: false,
+            } if union.concats.len() == 1 => {
+                basics.extend(union.concats.into_iter().next().unwrap().basics);
+            }
+            // r*r* -> r*
+            basic
+                if basic.is_iter && basics.last().is_some_and(|last| same_basic(last, &basic)) => {}
+            // (ε|r)r* -> r*
+            basic
+                if basics
+                    .last()
+                    .is_some_and(|last| is_optional_of(last, &basic)) =>
+            {
+                basics.pop();
+                basics.push(basic);
+            }
+            // r*(ε|r) -> r*
+            basic
+                if basics
+                    .last()
+                    .is_some_and(|last| is_optional_of(&basic, last)) => {}
+            basic => basics.push(basic),
+        }
+    }
+
+    Ok(Concat::new(basics))
+}
+
+// Basic
+
+fn simplify_basic(basic: Basic, budget: &Budget) -> Result<Basic, String> {
+    let mut atomic = match basic.atomic {
+        Atomic::Union(union) => Atomic::Union(simplify_union(union, budget)?),
+        atomic => atomic,
+    };
+    let mut is_iter = basic.is_iter;
+
+    if is_iter {
+        if let Atomic::Union(union) = atomic {
+            atomic = Atomic::Union(simplify_starred_union(union));
+        }
+    }
+
+    if let Atomic::Union(union) = &atomic {
+        match union.concats.as_slice() {
+            // (ε) -> ε
+            [] => atomic = Atomic::Epsilon,
+            [concat] if concat.basics.is_empty() => atomic = Atomic::Epsilon,
+            // (r) -> r, ((r*))* -> r*
+            [concat] if concat.basics.len() == 1 => {
+                let inner = concat.basics[0].clone();
+                is_iter |= inner.is_iter;
+                atomic = inner.atomic;
+            }
+            _ => {}
+        }
+    }
+
+    // ε* -> ε
+    if matches!(atomic, Atomic::Epsilon) {
+        is_iter = false;
+    }
+
+    Ok(Basic::new(atomic, is_iter))
+}
+
+// (ε|r)* -> r*, (r|s*)* -> (r|s)*
+fn simplify_starred_union(union: Union) -> Union {
+    let mut concats = Vec::new();
+
+    for concat in union.concats {
+        if concat.basics.is_empty() {
+            continue;
+        }
+
+        let concat = match concat.basics.as_slice() {
+            [basic] if basic.is_iter => Concat::new(vec![Basic::new(basic.atomic.clone(), false)]),
+            _ => concat,
+        };
+
+        if !concats.iter().any(|c| same_concat(c, &concat)) {
+            concats.push(concat);
+        }
+    }
+
+    Union::new(concats)
+}
+
+// `optional` is (ε|r) and `starred` is r*
+fn is_optional_of(optional: &Basic, starred: &Basic) -> bool {
+    let Atomic::Union(union) = &optional.atomic else {
+        return false;
+    };
+    if optional.is_iter || !starred.is_iter || !union.concats.iter().any(|c| c.basics.is_empty()) {
+        return false;
+    }
+
+    let branches: Vec<&Concat> = union
+        .concats
+        .iter()
+        .filter(|c| !c.basics.is_empty())
+        .collect();
+
+    match (&starred.atomic, branches.as_slice()) {
+        (Atomic::Union(operand), _) if operand.concats.len() == branches.len() => operand
+            .concats
+            .iter()
+            .zip(&branches)
+            .all(|(lhs, rhs)| same_concat(lhs, rhs)),
+        (atomic, [branch]) => starred_operand_equals(atomic, &branch.basics),
+        _ => false,
+    }
+}
+
+fn same_union(lhs: &Union, rhs: &Union) -> bool {
+    lhs.concats.len() == rhs.concats.len()
+        && lhs
+            .concats
+            .iter()
+            .zip(&rhs.concats)
+            .all(|(lhs, rhs)| same_concat(lhs, rhs))
+}
+
+fn same_concat(lhs: &Concat, rhs: &Concat) -> bool {
+    same_basics(&lhs.basics, &rhs.basics)
+}
+
+fn same_basics(lhs: &[Basic], rhs: &[Basic]) -> bool {
+    lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(lhs, rhs)| same_basic(lhs, rhs))
+}
+
+fn same_basic(lhs: &Basic, rhs: &Basic) -> bool {
+    lhs.is_iter == rhs.is_iter && same_atomic(&lhs.atomic, &rhs.atomic)
+}
+
+fn same_atomic(lhs: &Atomic, rhs: &Atomic) -> bool {
+    match (lhs, rhs) {
+        (Atomic::LinearizedSymbol(lhs), Atomic::LinearizedSymbol(rhs)) => lhs.symbol == rhs.symbol,
+        (Atomic::Epsilon, Atomic::Epsilon) => true,
+        (Atomic::Union(lhs), Atomic::Union(rhs)) => same_union(lhs, rhs),
+        _ => false,
+    }
+}
+
+fn does_epsilon_satisfy_concat(concat: &Concat) -> bool {
+    concat.basics.iter().all(|basic| {
+        basic.is_iter
+            || match &basic.atomic {
+                Atomic::LinearizedSymbol(_) => false,
+                Atomic::Epsilon => true,
+                Atomic::Union(union) => union.concats.iter().any(does_epsilon_satisfy_concat),
+            }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::simplify;
+    use crate::ndfa::ast::Tree;
+    use crate::ndfa::printer::Dialect;
+
+    fn simplified(regex: &str) -> String {
+        Dialect::Course.print(&simplify(&Tree::from_regex(regex)))
+    }
+
+    #[test]
+    fn idempotency() {
+        assert_eq!(simplified("a|b|a"), "a|b");
+        assert_eq!(simplified("(ab)*(ab)*"), "(ab)*");
+    }
+
+    #[test]
+    fn stars() {
+        assert_eq!(simplified("((a*))*"), "a*");
+        assert_eq!(simplified("(ε|a)*"), "a*");
+        assert_eq!(simplified("(a|b*)*"), "(a|b)*");
+        assert_eq!(simplified("ε*b"), "b");
+    }
+
+    #[test]
+    fn epsilon() {
+        assert_eq!(simplified("ε|aa*"), "a*");
+        assert_eq!(simplified("(ε|(ab)*ab)c"), "(ab)*c");
+        assert_eq!(simplified("ε|a*"), "a*");
+        assert_eq!(simplified("ε|a"), "ε|a");
+    }
+
+    #[test]
+    fn absorption() {
+        assert_eq!(simplified("a|a*"), "a*");
+        assert_eq!(simplified("(c|c*)b"), "c*b");
+        assert_eq!(simplified("(ab)*|ab|c"), "(ab)*|c");
+        assert_eq!(simplified("(ε|c)c*"), "c*");
+        assert_eq!(simplified("c*(ε|c)"), "c*");
+        assert_eq!(simplified("(ε|a|b)(a|b)*"), "(a|b)*");
+        assert_eq!(simplified("(ε|a)b*"), "(ε|a)b*");
+    }
+
+    #[test]
+    fn factoring() {
+        assert_eq!(simplified("ab|ac"), "a(b|c)");
+        assert_eq!(simplified("ba|ca"), "(b|c)a");
+        assert_eq!(simplified("abc|abd"), "ab(c|d)");
+        assert_eq!(simplified("ab|a"), "a(b|ε)");
+    }
+
+    #[test]
+    fn size() {
+        let tree = Tree::from_regex("(ab|ac)*");
+        assert_eq!(tree.size(), 8);
+        assert_eq!(simplify(&tree).size(), 6);
+    }
+}
@@ -0,0 +1,198 @@
+use std::fs;
+use std::path::Path;
+
+use super::dot::DotLabel;
+use super::limits;
+use super::{Automata, ToRegexOptions};
+
+/// How the transition incoming → outcoming was rebuilt when a state was eliminated.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransitionStep {
+    pub incoming: usize,
+    pub outcoming: usize,
+    pub former_regex: Option<String>,
+    pub incoming_regex: String,
+    pub cyclic_regex: Option<String>,
+    pub outcoming_regex: String,
+    pub result: String,
+    /// ε|r r* was folded to r*
+    pub is_unfolded: bool,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EliminationStep {
+    pub state: usize,
+    pub transitions: Vec<TransitionStep>,
+    pub automata: Automata<String>,
+    /// Numbers of the left states in the prepared automata
+    pub states: Vec<usize>,
+}
+
+/// States are numbered as in the prepared automata through the whole trace.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trace {
+    pub prepared: Automata<String>,
+    pub steps: Vec<EliminationStep>,
+    /// Before the simplification, None for the empty language
+    pub result: Option<String>,
+}
+
+#[cfg(feature = "serde")]
+impl super::serialization::Serialization for Trace {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TraceFormat {
+    Text,
+    #[cfg(feature = "serde")]
+    Json,
+    /// A DOT file for the prepared automata and for every step
+    Dot,
+}
+
+impl Automata {
+    /// Records `to_regex_with` by state elimination, `options.method` is ignored.
+    pub fn trace_elimination(&self, options: &ToRegexOptions) -> Trace {
+        let prepared = self.prepare_for_state_elimination();
+
+        let mut steps = Vec::new();
+        let mut states: Vec<usize> = (0..prepared.size).collect();
+
+        let result = limits::unlimited(|budget| {
+            prepared.clone().eliminate_states_in(
+                options.order,
+                options.search_width,
+                budget,
+                &mut |state, transitions, automata| {
+                    let transitions = transitions
+                        .into_iter()
+                        .map(|transition| TransitionStep {
+                            incoming: states[transition.incoming],
+                            outcoming: states[transition.outcoming],
+                            ..transition
+                        })
+                        .collect();
+
+                    // Eliminated states are swapped with the last ones
+                    let state = states.swap_remove(state);
+
+                    steps.push(EliminationStep {
+                        state,
+                        transitions,
+                        automata: automata.clone(),
+                        states: states.clone(),
+                    });
+                },
+            )
+        });
+
+        Trace {
+            prepared,
+            steps,
+            result,
+        }
+    }
+}
+
+impl Trace {
+    pub fn to_text(&self) -> String {
+        let mut text = format!("prepared automata with {} states\n", self.prepared.size);
+
+        for (i, step) in self.steps.iter().enumerate() {
+            text.push_str(&format!(
+                "step {}: eliminated state {}\n",
+                i + 1,
+                step.state
+            ));
+
+            for transition in &step.transitions {
+                text.push_str(&format!(
+                    "    {} -> {}: {}\n",
+                    transition.incoming,
+                    transition.outcoming,
+                    transition.describe()
+                ));
+            }
+        }
+
+        match &self.result {
+            Some(regex) => text.push_str(&format!("result: {}\n", regex.to_label())),
+            None => text.push_str("result: none, the language is empty\n"),
+        }
+
+        text
+    }
+
+    pub fn to_dot_frames(&self) -> Vec<String> {
+        let mut frames = vec![self.prepared.to_dot()];
+
+        for step in &self.steps {
+            frames.push(step.automata.to_dot_with_names(&step.states));
+        }
+
+        frames
+    }
+
+    pub fn write_dot_frames(&self, dir: &Path) -> Result<(), String> {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+
+        for (i, frame) in self.to_dot_frames().iter().enumerate() {
+            fs::write(dir.join(format!("elimination-{:03}.dot", i)), frame)
+                .map_err(|e| e.to_string())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TransitionStep {
+    fn describe(&self) -> String {
+        let mut parts = vec![format!("in {}", self.incoming_regex.to_label())];
+
+        if let Some(cyclic_regex) = &self.cyclic_regex {
+            parts.push(format!("loop {}", cyclic_regex.to_label()));
+        }
+        parts.push(format!("out {}", self.outcoming_regex.to_label()));
+        if let Some(former_regex) = &self.former_regex {
+            parts.push(format!("former {}", former_regex.to_label()));
+        }
+
+        let unfolded = if self.is_unfolded {
+            " (unfold axiom)"
+        } else {
+            ""
+        };
+
+        format!("{} => {}{}", parts.join(", "), self.result, unfolded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ndfa::{Automata, ToRegexOptions};
+
+    #[test]
+    fn original_numbers() {
+        // 0 -a-> 1 -b-> 2 -b-> 2, the final state 3 is added by the preparation
+        let trace = Automata::from_regex("ab*").trace_elimination(&ToRegexOptions::default());
+
+        let eliminated: Vec<usize> = trace.steps.iter().map(|step| step.state).collect();
+        assert_eq!(eliminated, vec![1, 2]);
+
+        // State 3 took the place of 1, but keeps its number
+        let transition = &trace.steps[1].transitions[0];
+        assert_eq!((transition.incoming, transition.outcoming), (0, 3));
+        assert_eq!(trace.steps[1].states, vec![0, 3]);
+    }
+
+    #[test]
+    fn unfold_axiom() {
+        let trace = Automata::from_regex("a*").trace_elimination(&ToRegexOptions::default());
+
+        assert!(trace.steps[0].transitions.iter().any(|t| t.is_unfolded));
+        assert_eq!(trace.result.as_deref(), Some("a*"));
+        assert_eq!(trace.to_dot_frames().len(), trace.steps.len() + 1);
+    }
+}
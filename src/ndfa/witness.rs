@@ -0,0 +1,164 @@
+use super::ast::{LinearizedSymbol, Tree};
+use super::{Automata, ARBITARY, START};
+
+/// Why the automata accepts or rejects a word.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Witness {
+    /// States passed by an accepting run, starting with START
+    Accepted { path: Vec<usize> },
+    /// The first `consumed` symbols were read and no run goes further, or none
+    /// of the states after the whole word is final
+    Rejected {
+        consumed: usize,
+        stuck_states: Vec<usize>,
+    },
+}
+
+impl Automata {
+    pub fn find_witness(&self, word: &str) -> Witness {
+        // Every state of a layer remembers the state of the previous layer it came from
+        let mut layers = vec![vec![(START, START)]];
+
+        for (consumed, symbol) in word.chars().enumerate() {
+            let mut next_layer = Vec::<(usize, usize)>::new();
+
+            for &(state, _) in layers.last().unwrap() {
+                for (next_state, symbol_opt) in self.transition_matrix[state].iter().enumerate() {
+                    let is_matched =
+                        matches!(symbol_opt, Some(s) if *s == symbol || *s == ARBITARY);
+
+                    if is_matched && !next_layer.iter().any(|&(s, _)| s == next_state) {
+                        next_layer.push((next_state, state));
+                    }
+                }
+            }
+
+            if next_layer.is_empty() {
+                return Witness::Rejected {
+                    consumed,
+                    stuck_states: get_states(layers.last().unwrap()),
+                };
+            }
+
+            layers.push(next_layer);
+        }
+
+        let last_layer = layers.last().unwrap();
+        let Some(&(finite_state, _)) = last_layer
+            .iter()
+            .find(|&&(state, _)| self.is_finite_state(state))
+        else {
+            return Witness::Rejected {
+                consumed: layers.len() - 1,
+                stuck_states: get_states(last_layer),
+            };
+        };
+
+        let mut path = vec![finite_state];
+        for layer in layers.iter().skip(1).rev() {
+            let current = *path.last().unwrap();
+            let &(_, previous) = layer.iter().find(|&&(state, _)| state == current).unwrap();
+            path.push(previous);
+        }
+        path.reverse();
+
+        Witness::Accepted { path }
+    }
+}
+
+fn get_states(layer: &[(usize, usize)]) -> Vec<usize> {
+    let mut states: Vec<usize> = layer.iter().map(|&(state, _)| state).collect();
+    states.sort_unstable();
+    states
+}
+
+impl Witness {
+    pub fn is_accepted(&self) -> bool {
+        matches!(self, Self::Accepted { .. })
+    }
+
+    /// With the tree of `Automata::from_regex`, the states are shown as the symbol positions.
+    pub fn describe(&self, word: &str, tree: Option<&Tree>) -> String {
+        let state_name =
+            |state: usize| match tree.and_then(|tree| tree.get_linearized_symbol(state)) {
+                Some(LinearizedSymbol { symbol, index }) => format!("{symbol}{index}"),
+                None => format!("q{state}"),
+            };
+        let state_names = |states: &[usize]| {
+            states
+                .iter()
+                .map(|&state| state_name(state))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        match self {
+            Self::Accepted { path } => format!("accepted by {}", state_names(path)),
+            Self::Rejected {
+                consumed,
+                stuck_states,
+            } => {
+                let prefix: String = word.chars().take(*consumed).collect();
+
+                if *consumed == word.chars().count() {
+                    format!(
+                        "rejected, the whole word leads to non-final states {}",
+                        state_names(stuck_states)
+                    )
+                } else {
+                    format!(
+                        "rejected after '{}', stuck in states {}",
+                        prefix,
+                        state_names(stuck_states)
+                    )
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Witness;
+    use crate::ndfa::ast::Tree;
+    use crate::ndfa::Automata;
+
+    #[test]
+    fn accepted() {
+        let automata = Automata::from_regex("(a|b)*a");
+        let witness = automata.find_witness("ba");
+
+        assert_eq!(
+            witness,
+            Witness::Accepted {
+                path: vec![0, 2, 3]
+            }
+        );
+        assert_eq!(
+            witness.describe("ba", Some(&Tree::from_regex("(a|b)*a"))),
+            "accepted by q0, b2, a3"
+        );
+    }
+
+    #[test]
+    fn rejected() {
+        let automata = Automata::from_regex("ab*c");
+
+        assert_eq!(
+            automata.find_witness("abbd"),
+            Witness::Rejected {
+                consumed: 3,
+                stuck_states: vec![2],
+            }
+        );
+        assert_eq!(
+            automata.find_witness("ab"),
+            Witness::Rejected {
+                consumed: 2,
+                stuck_states: vec![2],
+            }
+        );
+        assert!(automata.find_witness("ac").is_accepted());
+        assert!(Automata::from_regex("a.").find_witness("ax").is_accepted());
+    }
+}
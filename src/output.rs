@@ -0,0 +1,42 @@
+use std::fs;
+use std::path::Path;
+
+/// Output of the CLI commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    #[cfg(feature = "serde")]
+    Json,
+}
+
+/// Result of a CLI command, printed as text or as JSON.
+#[cfg(feature = "serde")]
+pub trait Output: serde::Serialize {
+    fn to_text(&self) -> String;
+}
+
+#[cfg(not(feature = "serde"))]
+pub trait Output {
+    fn to_text(&self) -> String;
+}
+
+pub fn print(value: &impl Output, format: OutputFormat) -> Result<(), String> {
+    let text = match format {
+        OutputFormat::Text => value.to_text(),
+        #[cfg(feature = "serde")]
+        OutputFormat::Json => serde_json::to_string(value).map_err(|e| e.to_string())? + "\n",
+    };
+
+    write(&text, None)
+}
+
+/// Prints the text to stdout if no file is given.
+pub fn write(text: &str, path: Option<&Path>) -> Result<(), String> {
+    match path {
+        Some(path) => fs::write(path, text).map_err(|e| e.to_string()),
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use std::fmt;
 use std::iter::Peekable;
 use std::str::Chars;
 use std::vec;
@@ -15,25 +16,25 @@ pub enum Token {
     CloseBracket,
 }
 
-impl Token {
-    pub fn to_string(&self) -> String {
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Token::Binary(s) => s.to_string(),
-            Token::Unary(s) => s.to_string(),
-            Token::SymbolSeq(s) => s.to_string(),
+            Token::Binary(s) => write!(f, "{}", s),
+            Token::Unary(s) => write!(f, "{}", s),
+            Token::SymbolSeq(s) => write!(f, "{}", s),
             Token::LookaheadGroup(l) => {
                 let mut r = "".to_string();
                 l.iter().for_each(|t| {
                     r += &t.to_string();
                 });
 
-                format!("(?={})", r)
+                write!(f, "(?={})", r)
             }
-            Token::LookaheadEnd => "".to_string(),
-            Token::Lookahead(_) => "".to_string(),
-            Token::StringEnd => "$".to_string(),
-            Token::OpenBracket => "(".to_string(),
-            Token::CloseBracket => ")".to_string(),
+            Token::LookaheadEnd => Ok(()),
+            Token::Lookahead(_) => Ok(()),
+            Token::StringEnd => write!(f, "$"),
+            Token::OpenBracket => write!(f, "("),
+            Token::CloseBracket => write!(f, ")"),
         }
     }
 }
@@ -121,21 +122,12 @@ fn parse_regex(stream: &mut Peekable<Chars<'_>>) -> Result<Vec<Token>, String> {
                     tokens.push(Token::SymbolSeq(regex));
                 }
 
-                // ^|a$ is invalid
-                if tokens.is_empty() {
-                    return Err(ERR_INVALID_OPERATION.to_string());
-                }
-
+                // ^|a$ and ^a|$ are valid: an empty branch stands for ε
                 tokens.push(Token::Binary("|".to_string()));
                 stream.next();
 
                 let mut tmp = parse_regex(stream)?;
 
-                // ^a|$ is invalid
-                if tmp.is_empty() {
-                    return Err(ERR_INVALID_OPERATION.to_string());
-                }
-
                 tokens.append(&mut tmp);
                 return Ok(tokens);
             }
@@ -187,8 +179,8 @@ fn simplify_brackets(tokens: Vec<Token>) -> Vec<Token> {
     let mut pairs = vec![];
     let mut tmp = vec![];
 
-    for i in 0..tokens.len() {
-        match &tokens[i] {
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
             Token::OpenBracket => {
                 stack.push(i);
             }
@@ -199,7 +191,7 @@ fn simplify_brackets(tokens: Vec<Token>) -> Vec<Token> {
         }
     }
 
-    pairs.sort_by(|a, b| a.0.cmp(&b.0));
+    pairs.sort_by_key(|a| a.0);
     for i in 1..pairs.len() {
         let prev = pairs[i - 1];
         let curr = pairs[i];
@@ -210,12 +202,10 @@ fn simplify_brackets(tokens: Vec<Token>) -> Vec<Token> {
         }
     }
 
-    let mut i = 0;
-    for t in tokens {
+    for (i, t) in tokens.into_iter().enumerate() {
         if !tmp.contains(&i) {
             result.push(t);
         }
-        i += 1;
     }
 
     result
@@ -322,18 +312,10 @@ fn parse_lookahead(stream: &mut Peekable<Chars<'_>>) -> Result<Vec<Token>, Strin
                     tokens.push(Token::SymbolSeq(lookahead));
                 }
 
-                if tokens.is_empty() {
-                    return Err(ERR_INVALID_OPERATION.to_string());
-                }
-
                 tokens.push(Token::Binary("|".to_string()));
                 stream.next();
                 let mut tmp = parse_lookahead(stream)?;
 
-                if tmp.is_empty() {
-                    return Err(ERR_INVALID_OPERATION.to_string());
-                }
-
                 tokens.append(&mut tmp);
 
                 return Ok(tokens);
@@ -432,14 +414,47 @@ mod tests {
     }
 
     #[test]
-    fn invalid_binary() {
+    fn empty_alternatives() {
         let regex = "^5|$";
-        assert!(parse(regex).is_err());
+        assert!(parse(regex).is_ok_and(|tokens| tokens.len() == 2
+            && matches!(tokens[0], Token::SymbolSeq { .. })
+            && matches!(tokens[1], Token::Binary { .. })));
 
         let regex = "^|6$";
-        assert!(parse(regex).is_err());
+        assert!(parse(regex).is_ok_and(|tokens| tokens.len() == 2
+            && matches!(tokens[0], Token::Binary { .. })
+            && matches!(tokens[1], Token::SymbolSeq { .. })));
 
         let regex = "^|$";
+        assert!(parse(regex).is_ok_and(|tokens| tokens.len() == 1));
+
+        let regex = "^(a|)b$";
+        assert!(parse(regex).is_ok_and(|tokens| tokens.len() == 5
+            && matches!(tokens[0], Token::OpenBracket)
+            && matches!(tokens[2], Token::Binary { .. })
+            && matches!(tokens[3], Token::CloseBracket)));
+
+        let regex = "^a(?=b|)$";
+        assert!(parse(regex).is_ok());
+    }
+
+    #[test]
+    fn explicit_epsilon() {
+        let regex = "^a(b|ε)$";
+        assert!(parse(regex).is_ok_and(
+            |tokens| tokens.len() == 6 && matches!(&tokens[4], Token::SymbolSeq(s) if s == "ε")
+        ));
+
+        let regex = "^ε*$";
+        assert!(parse(regex).is_ok());
+    }
+
+    #[test]
+    fn invalid_binary() {
+        let regex = "^|*$";
+        assert!(parse(regex).is_err());
+
+        let regex = "^a|*b$";
         assert!(parse(regex).is_err());
     }
 